@@ -3,543 +3,238 @@
 // stuff for objects
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-
-#[macro_export]
-macro_rules! vec_to_json_array {
-    ($($fname:ident($name:ident))*) => {
-       $(fn $fname(v: Vec<$name>) -> JsonValue {
-            let mut tmp: Vec<JsonValue> = vec![];
-            for value in v {
-                tmp.push(Custom::to_json(value));
-            }
-            let mut t = json::array![];
-            for x in tmp {
-                t.push(x).ok();
-            }
-            t
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! vec_vec_to_json_array {
-    ($($fname:ident($name:ident, $vec_func:ident))*) => {
-       $(fn $fname(v: Vec<Vec<$name>>) -> JsonValue {
-            let mut tmp: Vec<JsonValue> = vec![];
-            for value in v {
-                tmp.push($vec_func(value));
-            }
-            let mut t = json::array![];
-            for x in tmp {
-                t.push(x).ok();
-            }
-            t
-        })*
-    }
-}
-
+/// add_functionality creates structs deriving serde's `Serialize` and `Deserialize`.
+/// The field `typ` is renamed to `type` and fields with an `Option` are left out while `None`,
+/// all other fields are required when decoding. Fields which are not known to this crate yet are kept in `extra`.
 #[macro_export]
 macro_rules! add_functionality {
-    ($(pub struct $name:ident { $(pub $fname:ident : $ftype:ty), *})*) => {
-        $(pub struct $name {
-            $(pub $fname : $ftype), *
+    ($(pub struct $name:ident { $($fields:tt)* })*) => {
+        $(add_functionality! { @fields $name [] $($fields)* })*
+    };
+    (@fields $name:ident [$($(#[$attr:meta])* $fname:ident: $ftype:ty,)*]) => {
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        pub struct $name {
+            $($(#[$attr])* pub $fname: $ftype,)*
+            /// extra contains all fields, which are not known to this crate yet
+            #[serde(flatten)]
+            pub extra: serde_json::Map<String, serde_json::Value>,
         }
 
         impl $name {
+            /// from_json panics if `data` doesn't contain all required fields of this type
             pub fn from_json(data: JsonValue) -> $name {
-                let mut new = $name::empty();
-                $(
-                if stringify!($fname) == "typ" {
-                    new.$fname = Custom::from_json(data["type"].clone());
-                } else {
-                    new.$fname = Custom::from_json(data[stringify!($fname)].clone());
-                }); *;
-                new
-            }
-
-             pub fn to_json(&self) -> JsonValue {
-                let mut j = json::object! {};
-                $(if stringify!($fname) == "typ" {
-                    j = Custom::create_json(j, self.$fname.clone(), "type")
-                } else {
-                    j = Custom::create_json(j, self.$fname.clone(), stringify!($fname))
-                }); *;
-                j
-            }
-
-            pub fn empty() -> $name {
-                $name {
-                    $($fname: Custom::default(), )*
-                }
-            }
-        }
-        impl fmt::Display for $name {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                let mut data = vec![];
-                $(if stringify!($fname) == "typ" {
-                    data = Custom::push(data, self.$fname.clone(), "type")
-                } else {
-                    data = Custom::push(data, self.$fname.clone(), stringify!($fname))
-                }); *;
-                write!(f, "{}", data.join("; "))
-            }
-        }
-        impl Clone for $name {
-            fn clone(&self) -> Self {
-                $name {
-                    $($fname: self.$fname.clone()), *
-                }
-            }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! add_functionality_empty {
-    (pub struct $name:ident {}) => {
-        pub struct $name {}
-
-        impl $name {
-            pub fn from_json(_data: JsonValue) -> $name {
-                $name{}
+                serde_json::from_value($crate::objects::json_to_value(data))
+                    .unwrap_or_else(|e| panic!("can't decode {}: {}", stringify!($name), e))
             }
 
             pub fn to_json(&self) -> JsonValue {
-                JsonValue::new_object()
+                $crate::objects::to_json_value(self)
             }
 
             pub fn empty() -> $name {
-                $name{}
+                $name::default()
             }
         }
         impl fmt::Display for $name {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "{}", "{}")
+                write!(f, "{}", $crate::objects::fields_to_string(self))
             }
         }
-        impl Clone for $name {
-            fn clone(&self) -> Self {
-                $name {}
+        impl From<$name> for JsonValue {
+            fn from(v: $name) -> Self {
+                v.to_json()
             }
         }
-    }
-}
-
-#[macro_export]
-macro_rules! custom_from_json {
-    (fn from_json(s: JsonValue, $func:ident, $extract:ident) -> $fname:ident) => {
-        fn from_json(s: JsonValue) -> $fname {
-            s.$func().$extract()
+    };
+    (@fields $name:ident [$($done:tt)*] pub typ: Option<$t:ty> $(, $($rest:tt)*)?) => {
+        add_functionality! {
+            @fields $name [$($done)* #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")] typ: Option<$t>,] $($($rest)*)?
         }
-    }
-}
-
-#[macro_export]
-macro_rules! custom_push {
-    (fn push()) => {
-        fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-            data.push(format!("{}: {}", name, v));
-            data
+    };
+    (@fields $name:ident [$($done:tt)*] pub typ: $t:ty $(, $($rest:tt)*)?) => {
+        add_functionality! {
+            @fields $name [$($done)* #[serde(rename = "type")] typ: $t,] $($($rest)*)?
         }
-    }
-}
-
-#[macro_export]
-macro_rules! custom_default {
-    (fn default($default:expr)) => {
-        fn default() -> Self {
-            $default
+    };
+    (@fields $name:ident [$($done:tt)*] pub $fname:ident: Option<$t:ty> $(, $($rest:tt)*)?) => {
+        add_functionality! {
+            @fields $name [$($done)* #[serde(default, skip_serializing_if = "Option::is_none")] $fname: Option<$t>,] $($($rest)*)?
         }
-    }
-}
-
-#[macro_export]
-macro_rules! custom_url_encode {
-    (fn url_encode(v: Self)) => {
-        fn url_encode(v: Self) -> String {
-            urlencoding::encode(&*format!("{}",Custom::to_json(v)))
+    };
+    (@fields $name:ident [$($done:tt)*] pub $fname:ident: $t:ty $(, $($rest:tt)*)?) => {
+        add_functionality! {
+            @fields $name [$($done)* $fname: $t,] $($($rest)*)?
         }
-    }
+    };
 }
 
+/// add_functionality_enum creates an enum wrapping objects created by add_functionality.
+/// Each variant is picked when decoding if the JSON has the given tag (if any), e.g. `"type": "article"`,
+/// and contains the given key, the first matching variant wins. The tag is not kept in the wrapped object.
+/// An enum ending with `; Unknown` keeps JSON matching no variant as `serde_json::Value` instead of failing.
 #[macro_export]
-macro_rules! expand_custom {
-    ($(impl Custom for $fname:ident ($func:ident, $extract:ident, $default:expr))*) => {
-        $(impl Custom for $fname {
-            custom_from_json! {
-                fn from_json(s: JsonValue, $func, $extract) -> $fname
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, format!("{}", v)).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::String(format!("{}", v))
-            }
-            custom_push! {
-                fn push()
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
-            }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_custom_direct_i {
-    ($(impl Custom for $fname:ident ($func:ident, $extract:ident, $default:expr))*) => {
-        $(impl Custom for $fname {
-            custom_from_json! {
-                fn from_json(s: JsonValue, $func, $extract) -> $fname
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, v).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::Number(json::number::Number::from(v as i64))
-            }
-            custom_push! {
-                fn push()
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
-            }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_custom_direct_bool {
-    ($(impl Custom for $fname:ident ($func:ident, $extract:ident, $default:expr))*) => {
-        $(impl Custom for $fname {
-            custom_from_json! {
-                fn from_json(s: JsonValue, $func, $extract) -> $fname
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, v).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::Boolean(v)
-            }
-            custom_push! {
-                fn push()
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
-            }
-        })*
-    }
-}
+macro_rules! add_functionality_enum {
+    ($(pub enum $name:ident {
+        $first:ident($ftype:ident) $(= $ftag_name:literal: $ftag:literal)? if $fkey:literal
+        $(, $variant:ident($vtype:ident) $(= $tag_name:literal: $tag:literal)? if $key:literal)*
+        $(; $unknown:ident)?
+    })*) => {
+        $(#[derive(Debug, Clone)]
+        pub enum $name {
+            $first($ftype),
+            $($variant($vtype),)*
+            $(/// a variant which is not known to this crate yet
+            $unknown(serde_json::Value))?
+        }
 
-#[macro_export]
-macro_rules! expand_custom_direct_object {
-    ($(impl Custom for $fname:ident ($func:ident, $extract:ident, $default:expr))*) => {
-        $(impl Custom for $fname {
-            custom_from_json! {
-                fn from_json(s: JsonValue, $func, $extract) -> $fname
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, v).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                v.to_json()
-            }
-            custom_push! {
-                fn push()
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
+        impl $name {
+            /// from_json panics if `data` doesn't match any variant
+            pub fn from_json(data: JsonValue) -> $name {
+                serde_json::from_value($crate::objects::json_to_value(data))
+                    .unwrap_or_else(|e| panic!("can't decode {}: {}", stringify!($name), e))
             }
-        })*
-    }
-}
 
-#[macro_export]
-macro_rules! expand_custom_vec {
-    ($(impl Custom for Vec<$fname:ident> ($func:ident, $extract:ident, $default:expr, $to_json_array:ident))*) => {
-        $(impl Custom for Vec<$fname> {
-            fn from_json(s: JsonValue) -> Vec<$fname>{
-                if !s.is_array() { [].to_vec() }
-                else {s.$func().$extract()}
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, $to_json_array(v)).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::String(format!("{}", $to_json_array(v)))
-            }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                data.push(format!("{}: {}", name, $to_json_array(v)));
-                data
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
+            pub fn to_json(&self) -> JsonValue {
+                $crate::objects::to_json_value(self)
             }
-        })*
-    }
-}
 
-#[macro_export]
-macro_rules! expand_custom_vec_vec {
-    ($(impl Custom for Vec<Vec<$fname:ident>> ($func:ident, $extract:ident, $default:expr, $to_json_array:ident))*) => {
-        $(impl Custom for Vec<Vec<$fname>> {
-            fn from_json(s: JsonValue) -> Vec<Vec<$fname>> {
-                if !s.is_array() { $default }
-                else {s.$func().$extract()}
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str)  -> JsonValue {
-                j.insert(name, $to_json_array(v)).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::String(format!("{}", $to_json_array(v)))
-            }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                data.push(format!("{}: {}", name, $to_json_array(v)));
-                data
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
+            pub fn empty() -> $name {
+                $name::default()
             }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_custom_option {
-    ($(impl Custom for Option<$fname:ident> ($func:ident, $extract:ident, $default: expr))*) => {
-        $(impl Custom for Option<$fname> {
-            fn from_json(s: JsonValue) -> Option<$fname> {
-                if s.is_empty() && s.as_bool() != Some(false) { None }
-                else {Some(s.$func().$extract())}
+        }
+        impl Default for $name {
+            fn default() -> Self {
+                $name::$first($ftype::default())
             }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str) -> JsonValue {
-                match v.clone() {
-                    Some(value) => {j.insert(name, value).ok();},
-                    _ => ()
+        }
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut j = serde_json::Map::new();
+                let inner = match self {
+                    $name::$first(v) => {
+                        $(j.insert($ftag_name.to_string(), $ftag.into());)?
+                        serde_json::to_value(v)
+                    },
+                    $($name::$variant(v) => {
+                        $(j.insert($tag_name.to_string(), $tag.into());)?
+                        serde_json::to_value(v)
+                    },)*
+                    $($name::$unknown(v) => Ok(v.clone()))?
                 };
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                match v.clone() {
-                    Some(value) => JsonValue::String(format!("{}", value)),
-                    _ => JsonValue::Null
+                if let serde_json::Value::Object(inner) = inner.map_err(serde::ser::Error::custom)? {
+                    j.extend(inner);
                 }
-
-            }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                match v.clone() {Some(value) => data.push(format!("{}: {}", name, value)), _ => () };
-                data
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
-            }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_custom_box {
-    ($(impl Custom for Box<$fname:ident> ($func:ident, $extract:ident, $default: expr))*) => {
-        $(impl Custom for Box<$fname> {
-            fn from_json(s: JsonValue) -> Box<$fname> {
-                if s.is_empty() && s.as_bool() != Some(false) { Box::new($fname::empty()) }
-                else {s.$func().$extract()}
-            }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str) -> JsonValue {
-                j.insert(name, *v).ok();
-                j
-            }
-            fn to_json(v: Self)  -> JsonValue {
-                JsonValue::String(format!("{}", *v))
-            }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                data.push(format!("{}: {}", name, *v));
-                data
-            }
-            custom_default! {
-                fn default($default)
-            }
-            custom_url_encode! {
-                fn url_encode(v: Self)
-            }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_custom_option_box {
-    ($(impl Custom for Option<Box<$fname:ident>> ($func:ident, $extract:ident, $default: expr))*) => {
-        $(impl Custom for Option<Box<$fname>> {
-            fn from_json(s: JsonValue) -> Option<Box<$fname>> {
-                if s.is_empty() && s.as_bool() != Some(false) { None }
-                else {Some(s.$func().$extract())}
+                j.serialize(serializer)
             }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str) -> JsonValue {
-                match v.clone() {
-                    Some(value) => {j.insert(name, *value).ok();},
-                    _ => ()
-                };
-                j
+        }
+        impl<'de> Deserialize<'de> for $name {
+            #[allow(unreachable_code)]
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let data = serde_json::Value::deserialize(deserializer)?;
+                if $(data[$ftag_name] == $ftag &&)? !data[$fkey].is_null() {
+                    $(let data = $crate::objects::without_field(data, $ftag_name);)?
+                    return serde_json::from_value(data).map($name::$first).map_err(serde::de::Error::custom);
+                }
+                $(if $(data[$tag_name] == $tag &&)? !data[$key].is_null() {
+                    $(let data = $crate::objects::without_field(data, $tag_name);)?
+                    return serde_json::from_value(data).map($name::$variant).map_err(serde::de::Error::custom);
+                })*
+                $(return Ok($name::$unknown(data));)?
+                Err(serde::de::Error::custom(concat!("can't find this ", stringify!($name))))
             }
-            fn to_json(v: Self)  -> JsonValue {
-                match v.clone() {
-                    Some(value) => JsonValue::String(format!("{}", *value)),
-                    _ => JsonValue::Null
+        }
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    $name::$first(v) => write!(f, "{}", v),
+                    $($name::$variant(v) => write!(f, "{}", v),)*
+                    $($name::$unknown(v) => write!(f, "{}", v))?
                 }
-
             }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                match v.clone() {Some(value) => data.push(format!("{}: {}", name, *value)), _ => () };
-                data
+        }
+        impl From<$name> for JsonValue {
+            fn from(v: $name) -> Self {
+                v.to_json()
             }
-            custom_default! {
-                fn default($default)
+        }
+        impl From<$ftype> for $name {
+            fn from(v: $ftype) -> Self {
+                $name::$first(v)
             }
-            custom_url_encode! {
-                fn url_encode(v: Self)
+        }
+        $(impl From<$vtype> for $name {
+            fn from(v: $vtype) -> Self {
+                $name::$variant(v)
             }
-        })*
+        })*)*
     }
 }
 
+/// add_functionality_string_enum creates enums which are sent as plain strings, e.g. `"bot_command"`.
+/// Values which are not known to this crate are kept in `Unknown`, so new values added by Telegram
+/// don't fail decoding. The first variant is the default.
 #[macro_export]
-macro_rules! expand_custom_option_vec {
-    ($(impl Custom for Option<Vec<$fname:ident>> ($func:ident, $extract:ident, $default: expr, $to_json_array:ident))*) => {
-        $(impl Custom for Option<Vec<$fname>> {
-            fn from_json(s: JsonValue) -> Option<Vec<$fname>> {
-                if !s.is_array() { None }
-                else {Some(s.$func().$extract())}
+macro_rules! add_functionality_string_enum {
+    ($($(#[$doc:meta])* pub enum $name:ident {
+        $(#[$fdoc:meta])* $first:ident = $fvalue:literal
+        $(, $(#[$vdoc:meta])* $variant:ident = $value:literal)*
+    })*) => {
+        $($(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $(#[$fdoc])* $first,
+            $($(#[$vdoc])* $variant,)*
+            /// a value which is not known to this crate yet
+            Unknown(String)
+        }
+
+        impl $name {
+            /// as_str returns the value as it is sent to Telegram
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $name::$first => $fvalue,
+                    $($name::$variant => $value,)*
+                    $name::Unknown(s) => s
+                }
             }
-            fn create_json(mut j: JsonValue, v: Self, name: &'static str) -> JsonValue {
-                match v.clone() {
-                    Some(value) => {
-                        j.insert(name, $to_json_array(value)).ok();
-                    },
-                    _ => ()
-                };
-                j
+        }
+        impl Default for $name {
+            fn default() -> Self {
+                $name::$first
             }
-            fn to_json(v: Self)  -> JsonValue {
-                match v.clone() {
-                    Some(value) => JsonValue::String(format!("{}", $to_json_array(value))),
-                    _ => JsonValue::Null
+        }
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                match s {
+                    $fvalue => $name::$first,
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(s.to_string())
                 }
-
             }
-            fn push(mut data: Vec<String>, v: Self, name: &'static str) -> Vec<String> {
-                match v.clone() {
-                    Some(value) => {data.push(format!("{}: {:?}", name, $to_json_array(value)));},
-                    _ => ()
-                };
-                data
-            }
-            custom_default! {
-                fn default($default)
+        }
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                $name::from(s.as_str())
             }
-            custom_url_encode! {
-                fn url_encode(v: Self)
+        }
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.as_str())
             }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! as_custom {
-    ($(fn $fname:ident(&self) -> Option<$ftype:ident>)*) => {
-        $(fn $fname(&self) -> Option<$ftype> {
-            if self.is_empty() { None }
-            else { Some($ftype::from_json(self.clone())) }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! as_box_custom {
-    ($(fn $fname:ident(&self) -> Option<Box<$ftype:ident>>)*) => {
-        $(fn $fname(&self) -> Option<Box<$ftype>> {
-            if self.is_empty() { None }
-            else { Some(Box::new($ftype::from_json(self.clone()))) }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! as_vec_custom {
-    ($(fn $fname:ident(&self) -> Option<Vec<$ftype:ident>>)*) => {
-        $(fn $fname(&self) -> Option<Vec<$ftype>> {
-            if !self.is_array() { None }
-            else {
-                let mut ret: Vec<$ftype> = vec![];
-                let mut tmp: Vec<$ftype> = vec![];
-                let mut s = self.clone();
-                while !s.is_empty() {
-                    let t = s.pop();
-                    tmp.push($ftype::from_json(t))
-                }
-                while !tmp.is_empty() {
-                    ret.push(tmp.pop().unwrap())
-                }
-                Some(ret)
+        }
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
             }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! as_vec_vec_custom {
-    ($(fn $fname:ident(&self, $vec_func:ident) -> Option<Vec<Vec<$ftype:ident>>>)*) => {
-        $(fn $fname(&self) -> Option<Vec<Vec<$ftype>>> {
-            if !self.is_array() { None }
-            else {
-                let mut ret: Vec<Vec<$ftype>> = vec![];
-                let mut tmp: Vec<Vec<$ftype>> = vec![];
-                let mut s = self.clone();
-                while !s.is_empty() {
-                    let t = s.pop();
-                    tmp.push(t.$vec_func().unwrap())
-                }
-                while !tmp.is_empty() {
-                    ret.push(tmp.pop().unwrap())
-                }
-                Some(ret)
+        }
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name::from)
             }
-        })*
-    }
-}
-
-#[macro_export]
-macro_rules! expand_from {
-    ($(impl From<$fname:ident> for JsonValue)*) => {
-        $(impl From<$fname> for JsonValue {
-            fn from(u: $fname) -> Self {
-                u.to_json()
+        }
+        impl From<$name> for JsonValue {
+            fn from(v: $name) -> Self {
+                JsonValue::String(v.as_str().to_string())
             }
         })*
     }
@@ -564,54 +259,131 @@ macro_rules! expand_basic_test {
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 #[macro_export]
-macro_rules! expand_parameters_into_string {
-    ($parameters:ident, $($vname: ident), *) => {
-        $($ parameters.push_str(&*format!("{}={}&", stringify!($vname), Custom::url_encode($vname))));*
+macro_rules! expand_parameter {
+    ($($t:ty), *) => {
+        $(impl Parameter for $t {
+            fn add_to(self, name: &'static str, request: &mut Request) {
+                request.push(name, serde_json::to_value(self).unwrap_or_default())
+            }
+        })*
     }
 }
 
+/// expand_method_structs creates one request struct per method of the telegram_methods table.
+/// All parameters that are not an `Option` have to be passed to `new`, the others are set with builder functions.
 #[macro_export]
-macro_rules! expand_parameters_opt_into_string {
-    ($parameters:ident, $($vname: ident), *) => {
-        $(match $vname {
-            Some(v) => {
-                if stringify!($vname) == "typ" {
-                    $parameters.push_str(&*format!("typ={}&", Custom::url_encode(v)))
-                } else {
-                    $parameters.push_str(&*format!("{}={}&", stringify!($vname),Custom::url_encode(v)))
-                }},
-            None => ()
-        });*
+macro_rules! expand_method_structs {
+    ($($(#[$doc:meta])* fn $fname:ident($method:literal, $sname:ident) -> $ret:ty { $($params:tt)* })*) => {
+        $(expand_method_struct! {
+            @split $(#[$doc])* $sname($method) -> $ret [] [] $($params)*
+        })*
     }
 }
 
 #[macro_export]
-macro_rules! expand_parameters_reply_markup_into_string {
-    ($parameters:ident, $($vname: ident), *) => {
-        $(match $vname { Some(v) => $parameters.push_str(&*format!("reply_markup={}&", Custom::url_encode(v))), None => () });*
-    }
+macro_rules! expand_method_struct {
+    (@split $(#[$doc:meta])* $sname:ident($method:literal) -> $ret:ty
+        [$($rname:ident: $rtype:ty,)*] [$($oname:ident: $otype:ty,)*]) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $sname {
+            $(pub $rname: $rtype,)*
+            $(pub $oname: Option<$otype>,)*
+        }
+
+        #[allow(clippy::new_without_default)]
+        impl $sname {
+            pub fn new($($rname: impl Into<$rtype>), *) -> $sname {
+                $sname {
+                    $($rname: $rname.into(),)*
+                    $($oname: None,)*
+                }
+            }
+
+            $(pub fn $oname(mut self, $oname: impl Into<$otype>) -> $sname {
+                self.$oname = Some($oname.into());
+                self
+            })*
+        }
+
+        impl TelegramMethod for $sname {
+            type Response = $ret;
+
+            fn into_request(self) -> Request {
+                #[allow(unused_mut)]
+                let mut request = Request::new($method);
+                $(request.add(stringify!($rname), self.$rname);)*
+                $(request.add(stringify!($oname), self.$oname);)*
+                request
+            }
+
+            fn decode(res: serde_json::Value) -> Result<$ret, TelegramError> {
+                Ok(serde_json::from_value(res)?)
+            }
+        }
+    };
+    (@split $(#[$doc:meta])* $sname:ident($method:literal) -> $ret:ty [$($req:tt)*] [$($opt:tt)*]
+        $name:ident: Option<$t:ty> $(, $($rest:tt)*)?) => {
+        expand_method_struct! {
+            @split $(#[$doc])* $sname($method) -> $ret [$($req)*] [$($opt)* $name: $t,] $($($rest)*)?
+        }
+    };
+    (@split $(#[$doc:meta])* $sname:ident($method:literal) -> $ret:ty [$($req:tt)*] [$($opt:tt)*]
+        $name:ident: $t:ty $(, $($rest:tt)*)?) => {
+        expand_method_struct! {
+            @split $(#[$doc])* $sname($method) -> $ret [$($req)* $name: $t,] [$($opt)*] $($($rest)*)?
+        }
+    };
 }
 
 #[macro_export]
-macro_rules! expand_make_request_to_message {
-    ($res: ident) => {
-        if !$res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Message = Custom::from_json($res["result"].clone());
-            Some(ret)
+macro_rules! expand_blocking_methods {
+    ($($(#[$doc:meta])* fn $fname:ident($method:literal, $sname:ident) -> $ret:ty { $($params:tt)* })*) => {
+        impl Bot {
+            $(expand_wrapper_method! {
+                @blocking $(#[$doc])* $fname($sname) -> $ret [] [] $($params)*
+            })*
         }
     }
 }
 
 #[macro_export]
-macro_rules! expand_make_request_to_bool {
-    ($res: ident) => {
-        if !$res["ok"].as_bool().unwrap() {
-            false
-        } else {
-            let ret: bool = Custom::from_json($res["result"].clone());
-            ret
+macro_rules! expand_async_methods {
+    ($($(#[$doc:meta])* fn $fname:ident($method:literal, $sname:ident) -> $ret:ty { $($params:tt)* })*) => {
+        impl AsyncBot {
+            $(expand_wrapper_method! {
+                @async $(#[$doc])* $fname($sname) -> $ret [] [] $($params)*
+            })*
         }
     }
-}
\ No newline at end of file
+}
+
+/// expand_wrapper_method creates the method of `Bot` or `AsyncBot` sending the request struct `$sname`.
+/// Like `new` of the request structs, parameters that are not an `Option` take anything converting into their type.
+#[macro_export]
+macro_rules! expand_wrapper_method {
+    (@blocking $(#[$doc:meta])* $fname:ident($sname:ident) -> $ret:ty [$($vname:ident: $vtype:ty,)*] [$($field:tt)*]) => {
+        $(#[$doc])*
+        pub fn $fname(&mut self, $($vname: $vtype), *) -> Result<$ret, TelegramError> {
+            self.send($sname { $($field)* })
+        }
+    };
+    (@async $(#[$doc:meta])* $fname:ident($sname:ident) -> $ret:ty [$($vname:ident: $vtype:ty,)*] [$($field:tt)*]) => {
+        $(#[$doc])*
+        pub async fn $fname(&self, $($vname: $vtype), *) -> Result<$ret, TelegramError> {
+            self.send($sname { $($field)* }).await
+        }
+    };
+    (@$mode:ident $(#[$doc:meta])* $fname:ident($sname:ident) -> $ret:ty [$($arg:tt)*] [$($field:tt)*]
+        $name:ident: Option<$t:ty> $(, $($rest:tt)*)?) => {
+        expand_wrapper_method! {
+            @$mode $(#[$doc])* $fname($sname) -> $ret [$($arg)* $name: Option<$t>,] [$($field)* $name,] $($($rest)*)?
+        }
+    };
+    (@$mode:ident $(#[$doc:meta])* $fname:ident($sname:ident) -> $ret:ty [$($arg:tt)*] [$($field:tt)*]
+        $name:ident: $t:ty $(, $($rest:tt)*)?) => {
+        expand_wrapper_method! {
+            @$mode $(#[$doc])* $fname($sname) -> $ret [$($arg)* $name: impl Into<$t>,] [$($field)* $name: $name.into(),] $($($rest)*)?
+        }
+    };
+}
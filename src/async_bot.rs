@@ -0,0 +1,390 @@
+//! This module contains the async client, which is available with the `async` feature</br>
+//! It offers the same methods as `Bot`, but all of them return futures, e.g.:
+//! ```ignore
+//! let bot = AsyncBot::new("your_bot_api_key".to_string());
+//! let me = bot.get_me().await?;
+//! ```
+//! Instead of a channel, updates are received from a `Stream`:
+//! ```ignore
+//! let mut updates = Box::pin(bot.updates());
+//! while let Some(update) = updates.next().await {
+//!     println!("Got: {}", update);
+//! }
+//! ```
+#![allow(clippy::too_many_arguments)]
+
+use serde_json::Value;
+use crate::*;
+use crate::objects::*;
+use crate::error::TelegramError;
+use crate::methods::*;
+use crate::request::Request;
+use crate::offset::{AckHandle, AckedOffset, OffsetStore};
+use crate::polling::PollingConfig;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{Retry, RetryPolicy};
+use futures_util::stream::{self, Stream};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::multipart;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct AsyncBot {
+    key: String,
+    api_url: String,
+    offset: i64,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    offset_store: Option<AckedOffset>,
+    on_decode_error: Option<DecodeErrorHandler>,
+    client: reqwest::Client,
+}
+
+impl AsyncBot {
+    pub fn new(api_key: String) -> AsyncBot {
+        AsyncBot {
+            key: api_key,
+            api_url: API_URL.to_string(),
+            offset: 0,
+            retry_policy: None,
+            rate_limiter: None,
+            offset_store: None,
+            on_decode_error: None,
+            client: reqwest::Client::builder()
+                .connect_timeout(HTTP_TIMEOUT)
+                .build()
+                .expect("the HTTP client could not be initialized"),
+        }
+    }
+
+    /// with_api_url lets the bot talk to another server than api.telegram.org, e.g. a local Bot API server
+    pub fn with_api_url(mut self, api_url: &str) -> AsyncBot {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// with_retry_policy lets the bot wait for `retry_after` and follow chat migrations on its own
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> AsyncBot {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// with_rate_limiter queues all calls sending or editing messages to stay within the limits of `rate_limiter`
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> AsyncBot {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+        self
+    }
+
+    /// with_offset_store keeps the polling offset in `offset_store`, see the `offset` module.
+    /// Updates have to be acknowledged with `ack` afterwards, otherwise they are received again.
+    pub fn with_offset_store<S: OffsetStore + 'static>(mut self, offset_store: S) -> AsyncBot {
+        self.offset_store = Some(AckedOffset::new(offset_store));
+        self
+    }
+
+    /// ack commits the offset after `update` to the offset store, it does nothing without offset store
+    pub fn ack(&self, update: &Update) -> Result<(), TelegramError> {
+        self.ack_handle().ack(update)
+    }
+
+    /// ack_handle returns a handle acknowledging updates for this bot, e.g. for `Dispatcher::run`
+    pub fn ack_handle(&self) -> AckHandle {
+        AckHandle::new(self.offset_store.clone())
+    }
+
+    /// with_decode_error_handler calls `handler` with every received update, which could not be decoded.
+    /// Such updates are skipped, by default without any notice.
+    pub fn with_decode_error_handler<F: Fn(&Value, &TelegramError) + Send + Sync + 'static>(mut self, handler: F) -> AsyncBot {
+        self.on_decode_error = Some(Arc::new(handler));
+        self
+    }
+
+    /// updates polls for new updates using long polling with the default `PollingConfig` and yields them one by one.
+    /// Errors while fetching updates are skipped, polling continues after a backoff. Dropping the stream stops polling.
+    /// Errors which make polling impossible end the stream, they are passed to `PollingConfig::on_error`.
+    /// With an offset store, the next updates are only fetched after all yielded ones were acknowledged.
+    pub fn updates(&self) -> impl Stream<Item = Update> {
+        self.updates_with_config(PollingConfig::default())
+    }
+
+    /// updates_with_config is like updates, but with custom timeout, limit, allowed_updates, backoff and error handler
+    pub fn updates_with_config(&self, config: PollingConfig) -> impl Stream<Item = Update> {
+        let bot = self.clone();
+        stream::unfold((bot, config, VecDeque::new(), None), |(mut bot, config, mut buffer, mut next)| async move {
+            let mut failures = 0;
+            loop {
+                if let Some(u) = buffer.pop_front() {
+                    return Some((u, (bot, config, buffer, next)));
+                }
+                // with an offset store, the same updates would be received again until they are acknowledged
+                if let (Some(offset_store), Some(offset)) = (&bot.offset_store, next.take()) {
+                    while !offset_store.is_acked(offset) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+                match bot.get_updates(config.limit, Some(config.timeout), config.allowed_updates.clone()).await {
+                    Ok(updates) => {
+                        failures = 0;
+                        next = updates.last().map(|u| u.update_id + 1);
+                        buffer.extend(updates);
+                    },
+                    Err(error) => {
+                        if config.report(&error) {
+                            return None;
+                        }
+                        failures += 1;
+                        tokio::time::sleep(config.backoff(failures)).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// send sends any request struct and returns the decoded result
+    pub async fn send<M: TelegramMethod>(&self, method: M) -> Result<M::Response, TelegramError> {
+        let res = self.send_with_retries(method.into_request()).await?;
+        M::decode(res)
+    }
+
+    /// send_with_retries sends `request` again as long as the retry policy allows it
+    async fn send_with_retries(&self, mut request: Request) -> Result<Value, TelegramError> {
+        let policy = match &self.retry_policy {
+            Some(p) => p,
+            None => return self.send_request(request).await
+        };
+        let mut attempts = 1;
+        let mut waited = Duration::from_secs(0);
+        loop {
+            let error = match self.send_request(request.clone()).await {
+                Err(e) => e,
+                res => return res
+            };
+            match policy.next(&error, attempts, waited) {
+                Some(Retry::After(wait)) => {
+                    tokio::time::sleep(wait).await;
+                    waited += wait;
+                },
+                Some(Retry::Migrate(chat_id)) if request.migrate(chat_id) => {},
+                _ => return Err(error)
+            }
+            attempts += 1;
+        }
+    }
+
+    async fn send_request(&self, request: Request) -> Result<Value, TelegramError> {
+        if let Some(limiter) = &self.rate_limiter {
+            tokio::time::sleep(limiter.wait_time(&request)).await;
+        }
+        let url = request.url(&self.api_url, &self.key);
+        let timeout = request.long_poll_timeout().unwrap_or_default() + HTTP_TIMEOUT;
+        let res = if request.files.is_empty() {
+            self.client.post(url).header(CONTENT_TYPE, "application/json").body(request.body())
+                .timeout(timeout).send().await?
+        } else {
+            let mut form = multipart::Form::new();
+            for (name, value) in request.form_fields() {
+                form = form.text(name, value);
+            }
+            for (name, file) in request.files {
+                form = match file {
+                    InputFile::Path(path) => {
+                        let data = tokio::fs::read(&path).await?;
+                        let file_name = InputFile::Path(path).file_name();
+                        form.part(name, multipart::Part::bytes(data).file_name(file_name))
+                    },
+                    InputFile::Memory { file_name, data } => form.part(name, multipart::Part::bytes(data).file_name(file_name)),
+                    other => form.text(name, format!("{}", other))
+                };
+            }
+            self.client.post(url).multipart(form).timeout(timeout).send().await?
+        };
+        decode_response(&res.text().await?)
+    }
+
+    /// get_updates returns the updates after the last received one,
+    /// with an offset store after the last acknowledged one instead
+    pub async fn get_updates(&mut self, limit: Option<i32>, timeout: Option<i32>, allowed_updates: Option<Vec<String>>) -> Result<Vec<Update>, TelegramError> {
+        let mut request = Request::new("getUpdates");
+        match &self.offset_store {
+            Some(offset_store) => request.add("offset", offset_store.load()?),
+            None => request.add("offset", self.offset)
+        }
+        request.add("limit", limit);
+        request.add("timeout", timeout);
+        request.add("allowed_updates", allowed_updates);
+        let res = self.send_with_retries(request).await?;
+        let (ret, last) = decode_updates(res, self.offset_store.as_ref(), self.on_decode_error.as_ref())?;
+        if let (None, Some(last)) = (&self.offset_store, last) {
+            // the next request with this offset confirms the updates
+            self.offset = last + 1;
+        }
+        Ok(ret)
+    }
+
+    /// download_url returns the URL to download a file from, the file_path is taken from get_file.
+    /// The URL contains the bot token, so it should not be shared.
+    pub fn download_url(&self, file_path: &str) -> String {
+        format!("{}/file/bot{}/{}", self.api_url, self.key, file_path)
+    }
+
+    /// download_file streams a file received by get_file into `writer` and returns the number of bytes written.
+    /// `progress` is called after every chunk with the bytes downloaded so far and the total size, if known.
+    pub async fn download_file<W: Write>(&self, file: &File, writer: &mut W,
+                                         mut progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, TelegramError> {
+        let file_path = download_path(file)?;
+        let mut res = self.client.get(self.download_url(file_path)).send().await?.error_for_status()?;
+        let total = res.content_length().or_else(|| file.file_size.map(|s| s as u64));
+        let mut written = 0;
+        while let Some(chunk) = res.chunk().await? {
+            write_chunk(writer, &chunk, &mut written, total, &mut progress)?;
+        }
+        writer.flush()?;
+        Ok(written)
+    }
+}
+
+telegram_methods!(expand_async_methods);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offset::MemoryOffsetStore;
+    use crate::testing::fake_api;
+
+    #[tokio::test]
+    async fn test_async_get_me() {
+        let (url, handle) = fake_api(vec![r#"{"ok":true,"result":{"id":1234,"is_bot":true,"first_name":"Testbot"}}"#]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url);
+        let me = bot.get_me().await.unwrap();
+        assert_eq!(me.id, 1234);
+        assert_eq!(me.first_name, "Testbot");
+        assert!(handle.join().unwrap()[0].0.starts_with("POST /botKEY/getMe "));
+    }
+
+    #[tokio::test]
+    async fn test_async_api_error() {
+        let (url, handle) = fake_api(vec![r#"{"ok":false,"error_code":400,"description":"Bad Request: chat not found"}"#]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url);
+        match bot.send_message(12, "hi", None, None, None, None, None, None, None).await {
+            Err(TelegramError::Api { error_code, .. }) => assert_eq!(error_code, 400),
+            other => panic!("expected an api error, got {:?}", other.map(|m| m.message_id)),
+        }
+        let (request_line, request_body) = handle.join().unwrap().remove(0);
+        assert!(request_line.starts_with("POST /botKEY/sendMessage "));
+        assert_eq!(request_body, br#"{"chat_id":12,"text":"hi"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_async_upload() {
+        let (url, handle) = fake_api(vec![r#"{"ok":true,"result":true}"#]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url);
+        let photo = InputFile::Memory { file_name: "cat.jpg".to_string(), data: vec![1, 2, 3] };
+        assert!(bot.set_chat_photo(12, photo).await.unwrap());
+        let (request_line, request_body) = handle.join().unwrap().remove(0);
+        assert!(request_line.starts_with("POST /botKEY/setChatPhoto "));
+        assert!(String::from_utf8_lossy(&request_body).contains(r#"filename="cat.jpg""#));
+    }
+
+    #[tokio::test]
+    async fn test_async_retry() {
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":false,"error_code":429,"description":"Too Many Requests: retry after 0","parameters":{"retry_after":0}}"#,
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: group chat was upgraded to a supergroup chat","parameters":{"migrate_to_chat_id":-1001234}}"#,
+            r#"{"ok":true,"result":true}"#,
+        ]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url).with_retry_policy(RetryPolicy::default());
+        assert!(bot.send_chat_action(12, ChatAction::Typing).await.unwrap());
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[1].1, br#"{"chat_id":12,"action":"typing"}"#);
+        assert_eq!(requests[2].1, br#"{"chat_id":-1001234,"action":"typing"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_async_no_migration_with_from_chat_id() {
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: group chat was upgraded to a supergroup chat","parameters":{"migrate_to_chat_id":-1001234}}"#,
+        ]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url).with_retry_policy(RetryPolicy::default());
+        match bot.forward_message(12, -34, 1, None).await {
+            Err(TelegramError::Api { parameters: Some(p), .. }) => assert_eq!(p.migrate_to_chat_id, Some(-1001234)),
+            other => panic!("expected the migration error, got {:?}", other.map(|m| m.message_id)),
+        }
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_async_get_updates() {
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":true,"result":[{"update_id":5},{"update_id":6}]}"#,
+            r#"{"ok":true,"result":[]}"#,
+        ]);
+        let mut bot = AsyncBot::new("KEY".to_string()).with_api_url(&url);
+        assert_eq!(bot.get_updates(None, Some(0), None).await.unwrap().len(), 2);
+        assert_eq!(bot.get_updates(None, Some(0), None).await.unwrap().len(), 0);
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].1, br#"{"offset":0,"timeout":0}"#);
+        assert_eq!(requests[1].1, br#"{"offset":7,"timeout":0}"#);
+    }
+
+    #[tokio::test]
+    async fn test_async_offset_store() {
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":true,"result":[{"update_id":5}]}"#,
+            r#"{"ok":true,"result":[{"update_id":5}]}"#,
+            r#"{"ok":true,"result":[]}"#,
+        ]);
+        let mut bot = AsyncBot::new("KEY".to_string()).with_api_url(&url).with_offset_store(MemoryOffsetStore::new());
+        let updates = bot.get_updates(None, None, None).await.unwrap();
+        bot.get_updates(None, None, None).await.unwrap();
+        bot.ack(&updates[0]).unwrap();
+        bot.get_updates(None, None, None).await.unwrap();
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].1, br#"{"offset":0}"#);
+        assert_eq!(requests[1].1, br#"{"offset":0}"#);
+        assert_eq!(requests[2].1, br#"{"offset":6}"#);
+    }
+
+    #[tokio::test]
+    async fn test_async_updates_wait_for_ack() {
+        use futures_util::StreamExt;
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":true,"result":[{"update_id":5},{"update_id":6}]}"#,
+            r#"{"ok":true,"result":[{"update_id":7}]}"#,
+        ]);
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url).with_offset_store(MemoryOffsetStore::new());
+        let mut updates = Box::pin(bot.updates_with_config(PollingConfig { timeout: 0, ..PollingConfig::default() }));
+        let first = updates.next().await.unwrap();
+        let second = updates.next().await.unwrap();
+        bot.ack(&first).unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(300), updates.next()).await.is_err());
+        bot.ack(&second).unwrap();
+        assert_eq!(updates.next().await.unwrap().update_id, 7);
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0].1, br#"{"offset":0,"timeout":0}"#);
+        assert_eq!(requests[1].1, br#"{"offset":7,"timeout":0}"#);
+    }
+    #[tokio::test]
+    async fn test_async_updates_end_on_fatal_error() {
+        use futures_util::StreamExt;
+        let (url, handle) = fake_api(vec![
+            r#"{"ok":false,"error_code":502,"description":"Bad Gateway"}"#,
+            r#"{"ok":false,"error_code":401,"description":"Unauthorized"}"#,
+        ]);
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        let e = errors.clone();
+        let config = PollingConfig {
+            timeout: 0,
+            error_backoff: Duration::from_millis(10),
+            on_error: Some(Arc::new(move |error| e.lock().unwrap().push(error.to_string()))),
+            ..PollingConfig::default()
+        };
+        let bot = AsyncBot::new("KEY".to_string()).with_api_url(&url);
+        let updates = bot.updates_with_config(config);
+        assert!(updates.collect::<Vec<_>>().await.is_empty());
+        assert_eq!(errors.lock().unwrap().len(), 2);
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+}
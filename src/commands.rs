@@ -0,0 +1,330 @@
+//! This module contains the command router, which calls a handler for every registered bot command</br>
+//! Commands are found using the `bot_command` entities of a message and their arguments are parsed
+//! into typed values, e.g.:
+//! ```ignore
+//! let mut router = CommandRouter::from_bot(&bot)?;
+//! router.add("start", "Start the bot", |message, ()| println!("started in {}", message.chat.id));
+//! router.add("roll", "Roll a dice with n sides", |message, (sides,): (u32,)| println!("rolling a d{}", sides));
+//! router.add("say", "Repeat a text", |message, text: String| println!("{}", text));
+//! router.register(&bot)?;
+//! dispatcher.add(router.into_handler());
+//! ```
+//! `/cmd@other_bot` is ignored as soon as the router knows its own username, `/cmd@own_bot` is handled like `/cmd`.
+
+use crate::dispatcher::Handler;
+use crate::error::TelegramError;
+use crate::methods::{Bot, GetMe, SetMyCommands};
+use crate::objects::*;
+use std::str::FromStr;
+
+/// Command is a bot command at the start of a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    /// name of the command without the leading slash
+    pub name: String,
+    /// the bot the command was addressed to with `/name@bot_name`
+    pub bot_name: Option<String>,
+    /// everything after the command, trimmed
+    pub args: String,
+}
+
+impl Command {
+    /// parse returns the command the text or caption of `message` starts with, if any
+    pub fn parse(message: &Message) -> Option<Command> {
+        let (text, entities) = match (&message.text, &message.entities, &message.caption, &message.caption_entities) {
+            (Some(text), Some(entities), _, _) => (text, entities),
+            (_, _, Some(caption), Some(entities)) => (caption, entities),
+            _ => return None
+        };
+        let entity = entities.iter()
+            .find(|e| matches!(e.typ, MessageEntityType::BotCommand) && e.offset == 0)?;
+        let end = utf16_to_byte_index(text, entity.length as usize)?;
+        let (name, bot_name) = match text[..end].trim_start_matches('/').split_once('@') {
+            Some((name, bot_name)) => (name, Some(bot_name.to_string())),
+            None => (text[..end].trim_start_matches('/'), None)
+        };
+        Some(Command {
+            name: name.to_string(),
+            bot_name,
+            args: text[end..].trim().to_string(),
+        })
+    }
+
+    /// is_for checks whether the command is meant for the bot called `bot_name`,
+    /// commands without bot name are meant for every bot
+    pub fn is_for(&self, bot_name: &str) -> bool {
+        match &self.bot_name {
+            Some(b) => b.eq_ignore_ascii_case(bot_name.trim_start_matches('@')),
+            None => true
+        }
+    }
+}
+
+/// utf16_to_byte_index converts an offset as used by entities, which counts UTF-16 code units,
+/// to a byte index into `text`
+fn utf16_to_byte_index(text: &str, offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units == offset {
+            return Some(index);
+        }
+        units += c.len_utf16();
+    }
+    if units == offset { Some(text.len()) } else { None }
+}
+
+/// FromArgs turns the arguments of a command into a typed value.
+/// It is implemented for `()`, which accepts anything, `String`, which takes the arguments as they are,
+/// single values and tuples of values parsed with `FromStr` and `Vec`s of them.
+pub trait FromArgs: Sized {
+    /// from_args parses `args` or describes what is wrong with them
+    fn from_args(args: &str) -> Result<Self, String>;
+}
+
+impl FromArgs for () {
+    fn from_args(_: &str) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
+impl FromArgs for String {
+    fn from_args(args: &str) -> Result<Self, String> {
+        Ok(args.to_string())
+    }
+}
+
+impl<T: FromStr> FromArgs for Vec<T> {
+    fn from_args(args: &str) -> Result<Self, String> {
+        args.split_whitespace().map(parse_arg).collect()
+    }
+}
+
+fn parse_arg<T: FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse().map_err(|_| format!("invalid argument \"{}\"", arg))
+}
+
+fn split_args(args: &str, count: usize) -> Result<Vec<&str>, String> {
+    let args: Vec<&str> = args.split_whitespace().collect();
+    if args.len() == count {
+        Ok(args)
+    } else {
+        Err(format!("expected {} argument(s), got {}", count, args.len()))
+    }
+}
+
+macro_rules! expand_from_args {
+    ($($typ:ty),*) => {
+        $(impl FromArgs for $typ {
+            fn from_args(args: &str) -> Result<Self, String> {
+                parse_arg(split_args(args, 1)?[0])
+            }
+        })*
+    }
+}
+
+expand_from_args!(i32, i64, u32, u64, f64, bool);
+
+macro_rules! expand_from_args_tuple {
+    ($(($count:expr; $($name:ident: $index:tt),*))*) => {
+        $(impl<$($name: FromStr),*> FromArgs for ($($name,)*) {
+            fn from_args(args: &str) -> Result<Self, String> {
+                let args = split_args(args, $count)?;
+                Ok(($(parse_arg::<$name>(args[$index])?,)*))
+            }
+        })*
+    }
+}
+
+expand_from_args_tuple! {
+    (1; A: 0)
+    (2; A: 0, B: 1)
+    (3; A: 0, B: 1, C: 2)
+    (4; A: 0, B: 1, C: 2, D: 3)
+}
+
+type Route = Box<dyn FnMut(&Message, &str) -> Result<(), String> + Send>;
+type InvalidHandler = Box<dyn FnMut(&Message, &Command, String) + Send>;
+
+#[derive(Default)]
+pub struct CommandRouter {
+    bot_name: Option<String>,
+    commands: Vec<BotCommand>,
+    routes: Vec<Route>,
+    on_invalid: Option<InvalidHandler>,
+}
+
+impl CommandRouter {
+    pub fn new() -> CommandRouter {
+        CommandRouter::default()
+    }
+
+    /// from_bot creates a router, which ignores commands addressed to other bots than `bot`
+    pub fn from_bot(bot: &Bot) -> Result<CommandRouter, TelegramError> {
+        let me = bot.send(GetMe::new())?;
+        let mut router = CommandRouter::new();
+        router.bot_name = me.username;
+        Ok(router)
+    }
+
+    /// with_bot_name lets the router ignore commands addressed to other bots than `bot_name`
+    pub fn with_bot_name(mut self, bot_name: &str) -> CommandRouter {
+        self.bot_name = Some(bot_name.trim_start_matches('@').to_string());
+        self
+    }
+
+    /// add registers `handler` for `/command`, `description` is shown to users by `register`
+    pub fn add<A, F>(&mut self, command: &str, description: &str, mut handler: F) -> &mut CommandRouter
+        where A: FromArgs, F: FnMut(&Message, A) + Send + 'static {
+        self.commands.push(BotCommand { command: command.to_string(), description: description.to_string(), ..BotCommand::default() });
+        self.routes.push(Box::new(move |message, args| {
+            handler(message, A::from_args(args)?);
+            Ok(())
+        }));
+        self
+    }
+
+    /// on_invalid sets a handler for registered commands whose arguments could not be parsed,
+    /// e.g. to answer with the correct usage
+    pub fn on_invalid<F: FnMut(&Message, &Command, String) + Send + 'static>(&mut self, handler: F) -> &mut CommandRouter {
+        self.on_invalid = Some(Box::new(handler));
+        self
+    }
+
+    /// commands returns all registered commands
+    pub fn commands(&self) -> Vec<BotCommand> {
+        self.commands.clone()
+    }
+
+    /// register pushes all registered commands to Telegram using `set_my_commands`
+    pub fn register(&self, bot: &Bot) -> Result<bool, TelegramError> {
+        bot.send(SetMyCommands::new(self.commands()))
+    }
+
+    /// command returns the registered command `message` starts with, if it is meant for this bot
+    fn command(&self, message: &Message) -> Option<(usize, Command)> {
+        let command = Command::parse(message)?;
+        if let Some(bot_name) = &self.bot_name {
+            if !command.is_for(bot_name) {
+                return None;
+            }
+        }
+        let index = self.commands.iter().position(|c| c.command == command.name)?;
+        Some((index, command))
+    }
+
+    /// route calls the handler of the command `message` starts with and returns whether there was one
+    pub fn route(&mut self, message: &Message) -> bool {
+        let (index, command) = match self.command(message) {
+            Some(c) => c,
+            None => return false
+        };
+        if let Err(e) = (self.routes[index])(message, &command.args) {
+            if let Some(on_invalid) = &mut self.on_invalid {
+                on_invalid(message, &command, e);
+            }
+        }
+        true
+    }
+
+    /// into_handler turns the router into a message handler for the `Dispatcher`,
+    /// which only handles messages starting with a registered command
+    pub fn into_handler(self) -> Handler<Message> {
+        let mut router = self;
+        let matcher = CommandRouter {
+            bot_name: router.bot_name.clone(),
+            commands: router.commands.clone(),
+            ..CommandRouter::default()
+        };
+        Handler::message(move |m| { router.route(m); })
+            .filter(move |m| matcher.command(m).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::Dispatcher;
+    use std::sync::{Arc, Mutex};
+
+    fn message(text: &str, command_length: usize) -> Message {
+        let reference = format!(r#"{{"message_id":1,"date":0,"chat":{{"id":2,"type":"private"}},"text":"{}","entities":[{{"type":"bot_command","offset":0,"length":{}}}]}}"#,
+                                text, command_length);
+        Message::from_json(json::parse(&reference).unwrap())
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let command = Command::parse(&message("/roll@dice_bot 6  12", 14)).unwrap();
+        assert_eq!(command, Command { name: "roll".to_string(), bot_name: Some("dice_bot".to_string()), args: "6  12".to_string() });
+        assert!(command.is_for("@Dice_Bot"));
+        assert!(!command.is_for("other_bot"));
+        let command = Command::parse(&message("/start", 6)).unwrap();
+        assert_eq!(command.bot_name, None);
+        assert_eq!(command.args, "");
+        assert!(command.is_for("other_bot"));
+    }
+
+    #[test]
+    fn test_parse_command_utf16() {
+        // the emoji takes two UTF-16 code units, but four bytes
+        let command = Command::parse(&message("/say\u{1F600} hi", 6)).unwrap();
+        assert_eq!(command.name, "say\u{1F600}");
+        assert_eq!(command.args, "hi");
+    }
+
+    #[test]
+    fn test_no_command() {
+        let mut text = message("hello /start", 6);
+        text.entities.as_mut().unwrap()[0].offset = 6;
+        assert_eq!(Command::parse(&text), None);
+        let mut text = message("hello", 0);
+        text.entities = None;
+        assert_eq!(Command::parse(&text), None);
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(<(u32, String)>::from_args("6 dice"), Ok((6, "dice".to_string())));
+        assert_eq!(i64::from_args(" -12 "), Ok(-12));
+        assert_eq!(<Vec<i32>>::from_args("1 2 3"), Ok(vec![1, 2, 3]));
+        assert_eq!(String::from_args("hello world"), Ok("hello world".to_string()));
+        assert_eq!(<(u32,)>::from_args("six"), Err("invalid argument \"six\"".to_string()));
+        assert_eq!(<(u32, u32)>::from_args("6"), Err("expected 2 argument(s), got 1".to_string()));
+    }
+
+    #[test]
+    fn test_router() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut router = CommandRouter::new().with_bot_name("@dice_bot");
+        let s = seen.clone();
+        router.add("roll", "Roll a dice", move |_, (sides,): (u32,)| s.lock().unwrap().push(format!("roll {}", sides)));
+        let s = seen.clone();
+        router.on_invalid(move |_, command, e| s.lock().unwrap().push(format!("{}: {}", command.name, e)));
+        assert!(router.route(&message("/roll@dice_bot 6", 14)));
+        assert!(router.route(&message("/roll", 5)));
+        assert!(!router.route(&message("/roll@other_bot 6", 15)));
+        assert!(!router.route(&message("/start", 6)));
+        assert_eq!(*seen.lock().unwrap(), vec!["roll 6", "roll: expected 1 argument(s), got 0"]);
+        assert_eq!(format!("{}", router.commands()[0]), format!("{}", BotCommand { command: "roll".to_string(), description: "Roll a dice".to_string(), ..BotCommand::default() }));
+    }
+
+    #[test]
+    fn test_router_handler() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut router = CommandRouter::new();
+        let s = seen.clone();
+        router.add("say", "Repeat a text", move |_, text: String| s.lock().unwrap().push(text));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add(router.into_handler());
+        let s = seen.clone();
+        dispatcher.add(Handler::message(move |_| s.lock().unwrap().push("not a command".to_string())));
+        let update = |m: Message| {
+            let mut update = Update::empty();
+            update.message = Some(Box::new(m));
+            update
+        };
+        dispatcher.dispatch(&update(message("/say hello world", 4)));
+        dispatcher.dispatch(&update(message("/start", 6)));
+        assert_eq!(*seen.lock().unwrap(), vec!["hello world", "not a command"]);
+    }
+}
@@ -0,0 +1,430 @@
+//! This module contains conversations, which keep the state of multi-step dialogs across messages</br>
+//! Every conversation is keyed by chat and user, its state and the data collected so far are kept in a `Storage`, e.g.:
+//! ```ignore
+//! let mut conversation = Conversation::new(FileStorage::open("conversations.json")?);
+//! conversation.entry("register", |m, _| { ask(m, "What's your name?"); Transition::next("name") });
+//! conversation.state("name", |m, data| { data["name"] = m.text.clone().into(); ask(m, "How old are you?"); Transition::next("age") });
+//! conversation.state("age", |m, data| match m.text.as_deref().map(str::parse::<u32>) {
+//!     Some(Ok(age)) => { data["age"] = age.into(); Transition::End },
+//!     _ => { ask(m, "Please send a number"); Transition::Stay },
+//! });
+//! conversation.cancel_command("cancel");
+//! conversation.with_bot_name("my_bot");
+//! conversation.timeout(Duration::from_secs(600));
+//! dispatcher.add(Handler::message(move |m| { conversation.handle(m).ok(); }));
+//! ```
+//! Timeouts are measured with the dates of the messages and only checked when the next message of the user
+//! arrives, a conversation the user abandons stays in the storage and `on_timeout` is never called for it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::commands::Command;
+use crate::error::TelegramError;
+use crate::offset::write_atomically;
+use crate::objects::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// ConversationKey is the id of the chat and the id of the user a conversation is held with
+pub type ConversationKey = (i64, i64);
+
+/// ConversationError is returned by conversations, storage errors are wrapped as they are
+#[derive(Debug)]
+pub enum ConversationError {
+    /// the states could not be loaded or saved
+    Storage(Box<TelegramError>),
+    /// a conversation is in a state without handler, e.g. a stored state of an older version of the bot
+    UnknownState(String),
+}
+
+impl fmt::Display for ConversationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversationError::Storage(e) => write!(f, "storage error: {}", e),
+            ConversationError::UnknownState(state) => write!(f, "unknown conversation state \"{}\"", state),
+        }
+    }
+}
+
+impl std::error::Error for ConversationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConversationError::Storage(e) => Some(e.as_ref()),
+            ConversationError::UnknownState(_) => None,
+        }
+    }
+}
+
+impl From<TelegramError> for ConversationError {
+    fn from(e: TelegramError) -> Self {
+        ConversationError::Storage(Box::new(e))
+    }
+}
+
+/// ConversationState is what is stored for every running conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationState {
+    /// name of the current state
+    pub state: String,
+    /// data collected so far, a JSON object
+    pub data: Value,
+    /// date of the last message of the conversation
+    pub updated: i64,
+}
+
+/// Storage keeps the states of all running conversations
+pub trait Storage: Send {
+    fn load(&mut self, key: ConversationKey) -> Result<Option<ConversationState>, TelegramError>;
+    fn save(&mut self, key: ConversationKey, state: &ConversationState) -> Result<(), TelegramError>;
+    fn remove(&mut self, key: ConversationKey) -> Result<(), TelegramError>;
+}
+
+/// MemoryStorage keeps all states in memory, they are lost on restart
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    states: HashMap<ConversationKey, ConversationState>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&mut self, key: ConversationKey) -> Result<Option<ConversationState>, TelegramError> {
+        Ok(self.states.get(&key).cloned())
+    }
+
+    fn save(&mut self, key: ConversationKey, state: &ConversationState) -> Result<(), TelegramError> {
+        self.states.insert(key, state.clone());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: ConversationKey) -> Result<(), TelegramError> {
+        self.states.remove(&key);
+        Ok(())
+    }
+}
+
+/// FileStorage keeps all states in a JSON file, which is rewritten on every change
+#[derive(Debug)]
+pub struct FileStorage {
+    path: PathBuf,
+    states: MemoryStorage,
+}
+
+impl FileStorage {
+    /// open reads the states stored in `path`, a missing file is created on the first change
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileStorage, TelegramError> {
+        let path = path.as_ref().to_path_buf();
+        let mut states = MemoryStorage::new();
+        if path.exists() {
+            let stored: BTreeMap<String, ConversationState> = serde_json::from_slice(&fs::read(&path)?)?;
+            for (key, state) in stored {
+                let key = parse_key(&key)
+                    .ok_or_else(|| TelegramError::Decode(format!("invalid conversation key \"{}\"", key)))?;
+                states.states.insert(key, state);
+            }
+        }
+        Ok(FileStorage { path, states })
+    }
+
+    /// write replaces the file with write_atomically, so it is never left half written
+    fn write(&self) -> Result<(), TelegramError> {
+        let stored: BTreeMap<String, &ConversationState> = self.states.states.iter()
+            .map(|((chat_id, user_id), state)| (format!("{}:{}", chat_id, user_id), state))
+            .collect();
+        write_atomically(&self.path, &serde_json::to_vec(&stored)?)?;
+        Ok(())
+    }
+}
+
+fn parse_key(key: &str) -> Option<ConversationKey> {
+    let (chat_id, user_id) = key.split_once(':')?;
+    Some((chat_id.parse().ok()?, user_id.parse().ok()?))
+}
+
+impl Storage for FileStorage {
+    fn load(&mut self, key: ConversationKey) -> Result<Option<ConversationState>, TelegramError> {
+        self.states.load(key)
+    }
+
+    fn save(&mut self, key: ConversationKey, state: &ConversationState) -> Result<(), TelegramError> {
+        self.states.save(key, state)?;
+        self.write()
+    }
+
+    fn remove(&mut self, key: ConversationKey) -> Result<(), TelegramError> {
+        self.states.remove(key)?;
+        self.write()
+    }
+}
+
+/// Transition is returned by a state handler to tell where the conversation goes next
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    /// continue with the state of the given name
+    Next(String),
+    /// stay in the current state, e.g. after an invalid answer
+    Stay,
+    /// end the conversation and forget its data
+    End,
+}
+
+impl Transition {
+    pub fn next(state: &str) -> Transition {
+        Transition::Next(state.to_string())
+    }
+}
+
+type StateHandler = Box<dyn FnMut(&Message, &mut Value) -> Transition + Send>;
+type EndHandler = Box<dyn FnMut(&Message, &ConversationState) + Send>;
+
+pub struct Conversation<S: Storage> {
+    storage: S,
+    entries: HashMap<String, StateHandler>,
+    states: HashMap<String, StateHandler>,
+    cancel_commands: Vec<String>,
+    bot_name: Option<String>,
+    timeout: Option<Duration>,
+    on_cancel: Option<EndHandler>,
+    on_timeout: Option<EndHandler>,
+}
+
+impl<S: Storage> Conversation<S> {
+    pub fn new(storage: S) -> Conversation<S> {
+        Conversation {
+            storage,
+            entries: HashMap::new(),
+            states: HashMap::new(),
+            cancel_commands: vec![],
+            bot_name: None,
+            timeout: None,
+            on_cancel: None,
+            on_timeout: None,
+        }
+    }
+
+    /// entry starts a new conversation with `handler` whenever a user sends `/command`,
+    /// a conversation already running with that user is replaced
+    pub fn entry<F>(&mut self, command: &str, handler: F) -> &mut Conversation<S>
+        where F: FnMut(&Message, &mut Value) -> Transition + Send + 'static {
+        self.entries.insert(command.to_string(), Box::new(handler));
+        self
+    }
+
+    /// state sets the handler for all messages a user sends while the conversation is in `state`
+    pub fn state<F>(&mut self, state: &str, handler: F) -> &mut Conversation<S>
+        where F: FnMut(&Message, &mut Value) -> Transition + Send + 'static {
+        self.states.insert(state.to_string(), Box::new(handler));
+        self
+    }
+
+    /// cancel_command lets users end a running conversation with `/command`
+    pub fn cancel_command(&mut self, command: &str) -> &mut Conversation<S> {
+        self.cancel_commands.push(command.to_string());
+        self
+    }
+
+    /// with_bot_name lets entry and cancel commands addressed to other bots than `bot_name` be ignored
+    pub fn with_bot_name(&mut self, bot_name: &str) -> &mut Conversation<S> {
+        self.bot_name = Some(bot_name.trim_start_matches('@').to_string());
+        self
+    }
+
+    /// timeout ends conversations, whose last message is older than `timeout`.
+    /// It is checked when the next message of the user arrives, there is no timer ending conversations on its own.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Conversation<S> {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// on_cancel is called with the last state when a user cancels a conversation
+    pub fn on_cancel<F: FnMut(&Message, &ConversationState) + Send + 'static>(&mut self, handler: F) -> &mut Conversation<S> {
+        self.on_cancel = Some(Box::new(handler));
+        self
+    }
+
+    /// on_timeout is called with the last state when a conversation expired
+    pub fn on_timeout<F: FnMut(&Message, &ConversationState) + Send + 'static>(&mut self, handler: F) -> &mut Conversation<S> {
+        self.on_timeout = Some(Box::new(handler));
+        self
+    }
+
+    /// key returns the key of the conversation `message` belongs to
+    pub fn key(message: &Message) -> ConversationKey {
+        (message.chat.id, message.from.as_ref().map(|u| u.id).unwrap_or(0))
+    }
+
+    /// current returns the state of the conversation `message` belongs to, if there is one
+    pub fn current(&mut self, message: &Message) -> Result<Option<ConversationState>, ConversationError> {
+        Ok(self.storage.load(Conversation::<S>::key(message))?)
+    }
+
+    /// handle passes `message` to the matching entry or state handler and stores the new state.
+    /// It returns whether the message was part of a conversation.
+    pub fn handle(&mut self, message: &Message) -> Result<bool, ConversationError> {
+        let key = Conversation::<S>::key(message);
+        let command = Command::parse(message)
+            .filter(|c| self.bot_name.as_ref().map(|b| c.is_for(b)).unwrap_or(true))
+            .map(|c| c.name);
+        let mut current = self.storage.load(key)?;
+        if let (Some(state), Some(timeout)) = (&current, self.timeout) {
+            if (message.date as i64 - state.updated).max(0) as u64 > timeout.as_secs() {
+                self.storage.remove(key)?;
+                if let Some(on_timeout) = &mut self.on_timeout {
+                    on_timeout(message, state);
+                }
+                current = None;
+            }
+        }
+        if let Some(command) = &command {
+            if let Some(entry) = self.entries.get_mut(command) {
+                let mut data = Value::Object(Map::new());
+                let transition = entry(message, &mut data);
+                self.apply(key, message, None, data, transition)?;
+                return Ok(true);
+            }
+        }
+        let state = match current {
+            Some(s) => s,
+            None => return Ok(false)
+        };
+        if command.map(|c| self.cancel_commands.contains(&c)).unwrap_or(false) {
+            self.storage.remove(key)?;
+            if let Some(on_cancel) = &mut self.on_cancel {
+                on_cancel(message, &state);
+            }
+            return Ok(true);
+        }
+        let handler = match self.states.get_mut(&state.state) {
+            Some(h) => h,
+            None => return Err(ConversationError::UnknownState(state.state))
+        };
+        let mut data = state.data;
+        let transition = handler(message, &mut data);
+        self.apply(key, message, Some(state.state), data, transition)?;
+        Ok(true)
+    }
+
+    fn apply(&mut self, key: ConversationKey, message: &Message, current: Option<String>,
+             data: Value, transition: Transition) -> Result<(), ConversationError> {
+        let state = match (transition, current) {
+            (Transition::Next(next), _) => next,
+            (Transition::Stay, Some(current)) => current,
+            (Transition::Stay, None) | (Transition::End, _) => return Ok(self.storage.remove(key)?)
+        };
+        Ok(self.storage.save(key, &ConversationState { state, data, updated: message.date as i64 })?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn message(text: &str, date: i32) -> Message {
+        let entities = if text.starts_with('/') {
+            format!(r#","entities":[{{"type":"bot_command","offset":0,"length":{}}}]"#, text.len())
+        } else {
+            String::new()
+        };
+        let reference = format!(r#"{{"message_id":1,"date":{},"chat":{{"id":2,"type":"private"}},"from":{{"id":3,"is_bot":false,"first_name":"Test"}},"text":"{}"{}}}"#,
+                                date, text, entities);
+        Message::from_json(json::parse(&reference).unwrap())
+    }
+
+    fn register<S: Storage>(storage: S, ended: Arc<Mutex<Vec<String>>>) -> Conversation<S> {
+        let mut conversation = Conversation::new(storage);
+        conversation.entry("register", |_, _| Transition::next("name"));
+        conversation.state("name", |m, data| {
+            data["name"] = m.text.clone().into();
+            Transition::next("age")
+        });
+        conversation.state("age", move |m, data| match m.text.as_deref().map(str::parse::<u32>) {
+            Some(Ok(age)) => {
+                ended.lock().unwrap().push(format!("{} is {}", data["name"].as_str().unwrap(), age));
+                Transition::End
+            },
+            _ => Transition::Stay
+        });
+        conversation.cancel_command("cancel");
+        conversation.timeout(Duration::from_secs(60));
+        conversation
+    }
+
+    #[test]
+    fn test_conversation() {
+        let ended = Arc::new(Mutex::new(vec![]));
+        let mut conversation = register(MemoryStorage::new(), ended.clone());
+        assert!(!conversation.handle(&message("hello", 0)).unwrap());
+        assert!(conversation.handle(&message("/register", 10)).unwrap());
+        assert!(conversation.handle(&message("Alice", 20)).unwrap());
+        assert!(conversation.handle(&message("old", 30)).unwrap());
+        assert_eq!(conversation.current(&message("", 0)).unwrap().unwrap().state, "age");
+        assert!(conversation.handle(&message("42", 40)).unwrap());
+        assert_eq!(conversation.current(&message("", 0)).unwrap(), None);
+        assert_eq!(*ended.lock().unwrap(), vec!["Alice is 42"]);
+    }
+
+    #[test]
+    fn test_cancel_and_timeout() {
+        let ended = Arc::new(Mutex::new(vec![]));
+        let mut conversation = register(MemoryStorage::new(), ended.clone());
+        let e = ended.clone();
+        conversation.on_cancel(move |_, state| e.lock().unwrap().push(format!("cancelled in {}", state.state)));
+        let e = ended.clone();
+        conversation.on_timeout(move |_, state| e.lock().unwrap().push(format!("timed out in {}", state.state)));
+        conversation.handle(&message("/register", 0)).unwrap();
+        assert!(conversation.handle(&message("/cancel", 10)).unwrap());
+        assert!(!conversation.handle(&message("/cancel", 20)).unwrap());
+        conversation.handle(&message("/register", 30)).unwrap();
+        assert!(!conversation.handle(&message("Alice", 100)).unwrap());
+        assert_eq!(*ended.lock().unwrap(), vec!["cancelled in name", "timed out in name"]);
+    }
+
+    #[test]
+    fn test_bot_name() {
+        let ended = Arc::new(Mutex::new(vec![]));
+        let mut conversation = register(MemoryStorage::new(), ended);
+        conversation.with_bot_name("@my_bot");
+        assert!(!conversation.handle(&message("/register@other_bot", 0)).unwrap());
+        assert!(conversation.handle(&message("/register@my_bot", 10)).unwrap());
+        assert!(conversation.handle(&message("/cancel@other_bot", 20)).unwrap());
+        assert_eq!(conversation.current(&message("", 0)).unwrap().unwrap().state, "age");
+        assert!(conversation.handle(&message("/cancel", 30)).unwrap());
+        assert_eq!(conversation.current(&message("", 0)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unknown_state() {
+        let mut conversation = Conversation::new(MemoryStorage::new());
+        conversation.entry("start", |_, _| Transition::next("missing"));
+        conversation.handle(&message("/start", 0)).unwrap();
+        match conversation.handle(&message("hello", 10)) {
+            Err(ConversationError::UnknownState(state)) => assert_eq!(state, "missing"),
+            other => panic!("expected an unknown state, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let path = std::env::temp_dir().join(format!("telegram-api-rs-conversations-{}.json", std::process::id()));
+        let ended = Arc::new(Mutex::new(vec![]));
+        let mut conversation = register(FileStorage::open(&path).unwrap(), ended.clone());
+        conversation.handle(&message("/register", 0)).unwrap();
+        conversation.handle(&message("Bob", 10)).unwrap();
+        let mut storage = FileStorage::open(&path).unwrap();
+        let state = storage.load((2, 3)).unwrap().unwrap();
+        assert_eq!(state, ConversationState { state: "age".to_string(), data: serde_json::json!({ "name": "Bob" }), updated: 10 });
+        let mut conversation = register(storage, ended.clone());
+        conversation.handle(&message("7", 20)).unwrap();
+        assert_eq!(FileStorage::open(&path).unwrap().load((2, 3)).unwrap(), None);
+        assert_eq!(*ended.lock().unwrap(), vec!["Bob is 7"]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+//! This module contains the dispatcher, which hands every update to the first matching handler</br>
+//! Handlers are registered per update kind and receive the typed content of the update, e.g.:
+//! ```ignore
+//! let mut dispatcher = Dispatcher::new();
+//! dispatcher.add(Handler::message(|m| println!("Got: {}", m)).filter(|m| m.text.is_some()));
+//! dispatcher.add(Handler::callback_query(|q| println!("Clicked: {}", q.id)));
+//! dispatcher.add(Handler::update(|u| println!("Unhandled: {}", u)).order(100));
+//! ```
+//! The dispatcher consumes the same channel as `Bot::start_polling` and `WebhookServer`:
+//! ```ignore
+//! let (tx, rx) = mpsc::channel();
+//! bot.start_polling(tx);
+//! dispatcher.run(rx, &bot.ack_handle())?;
+//! ```
+//! Every update is acknowledged once its handler returned, so polling with an offset store goes on.
+//! Handlers are tried by ascending `order`, handlers with the same order in the order they were added.
+//! The first handler whose kind and filters match handles the update, all later handlers are skipped.
+
+use crate::error::TelegramError;
+use crate::objects::*;
+use crate::offset::AckHandle;
+use std::borrow::Borrow;
+use std::sync::mpsc::Receiver;
+
+type Filter<T> = Box<dyn Fn(&T) -> bool + Send>;
+
+/// Handler is a callback for one kind of update, together with its filters and its order
+pub struct Handler<T: 'static> {
+    extract: fn(&Update) -> Option<&T>,
+    filters: Vec<Filter<T>>,
+    callback: Box<dyn FnMut(&T) + Send>,
+    order: i32,
+}
+
+impl<T: 'static> Handler<T> {
+    fn new<F: FnMut(&T) + Send + 'static>(extract: fn(&Update) -> Option<&T>, callback: F) -> Handler<T> {
+        Handler {
+            extract,
+            filters: vec![],
+            callback: Box::new(callback),
+            order: 0,
+        }
+    }
+
+    /// filter only lets the handler handle updates for which `filter` returns true, filters can be chained
+    pub fn filter<F: Fn(&T) -> bool + Send + 'static>(mut self, filter: F) -> Handler<T> {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// order sets when the handler is tried, lower orders first, the default is 0
+    pub fn order(mut self, order: i32) -> Handler<T> {
+        self.order = order;
+        self
+    }
+}
+
+impl Handler<Update> {
+    /// update handles any kind of update, e.g. as fallback with a high order
+    pub fn update<F: FnMut(&Update) + Send + 'static>(callback: F) -> Handler<Update> {
+        Handler::new(|u| Some(u), callback)
+    }
+}
+
+macro_rules! expand_handlers {
+    ($(fn $name:ident($field:ident) -> $typ:ident)*) => {
+        $(impl Handler<$typ> {
+            pub fn $name<F: FnMut(&$typ) + Send + 'static>(callback: F) -> Handler<$typ> {
+                Handler::new(|u| u.$field.as_ref().map(Borrow::<$typ>::borrow), callback)
+            }
+        })*
+    }
+}
+
+expand_handlers! {
+    fn message(message) -> Message
+    fn edited_message(edited_message) -> Message
+    fn channel_post(channel_post) -> Message
+    fn edited_channel_post(edited_channel_post) -> Message
+    fn inline_query(inline_query) -> InlineQuery
+    fn chosen_inline_result(chosen_inline_result) -> ChosenInlineResult
+    fn callback_query(callback_query) -> CallbackQuery
+    fn shipping_query(shipping_query) -> ShippingQuery
+    fn pre_checkout_query(pre_checkout_query) -> PreCheckoutQuery
+    fn poll(poll) -> Poll
+    fn poll_answer(poll_answer) -> PollAnswer
+    fn my_chat_member(my_chat_member) -> ChatMemberUpdated
+    fn chat_member(chat_member) -> ChatMemberUpdated
+}
+
+struct Entry {
+    order: i32,
+    handle: Box<dyn FnMut(&Update) -> bool + Send>,
+}
+
+#[derive(Default)]
+pub struct Dispatcher {
+    entries: Vec<Entry>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Dispatcher {
+        Dispatcher::default()
+    }
+
+    /// add registers `handler`, see the module documentation for the order handlers are tried in
+    pub fn add<T: 'static>(&mut self, handler: Handler<T>) -> &mut Dispatcher {
+        let Handler { extract, filters, mut callback, order } = handler;
+        let handle = move |update: &Update| match extract(update) {
+            Some(content) if filters.iter().all(|f| f(content)) => {
+                callback(content);
+                true
+            },
+            _ => false
+        };
+        let index = self.entries.partition_point(|e| e.order <= order);
+        self.entries.insert(index, Entry { order, handle: Box::new(handle) });
+        self
+    }
+
+    /// dispatch hands `update` to the first matching handler and returns whether there was one
+    pub fn dispatch(&mut self, update: &Update) -> bool {
+        self.entries.iter_mut().any(|e| (e.handle)(update))
+    }
+
+    /// run dispatches all updates received from `rx` until all senders are gone and acknowledges each one with `ack`
+    /// after it was handled. It stops at the first update, which can't be acknowledged.
+    pub fn run(&mut self, rx: Receiver<Update>, ack: &AckHandle) -> Result<(), TelegramError> {
+        for update in rx {
+            self.dispatch(&update);
+            ack.ack(&update)?;
+        }
+        Ok(())
+    }
+
+    /// run_stream dispatches all updates of a stream like `AsyncBot::updates` until it ends, like `run`
+    #[cfg(feature = "async")]
+    pub async fn run_stream<S: futures_util::Stream<Item = Update>>(&mut self, updates: S, ack: &AckHandle) -> Result<(), TelegramError> {
+        use futures_util::StreamExt;
+        futures_util::pin_mut!(updates);
+        while let Some(update) = updates.next().await {
+            self.dispatch(&update);
+            ack.ack(&update)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::Bot;
+    use crate::offset::MemoryOffsetStore;
+    use crate::testing::fake_api;
+    use serde_json::Value;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    fn update(reference: &str) -> Update {
+        Update::from_json(json::parse(reference).unwrap())
+    }
+
+    fn text_message() -> Update {
+        update(r#"{"update_id":1,"message":{"message_id":2,"date":0,"chat":{"id":3,"type":"private"},"text":"hello"}}"#)
+    }
+
+    fn callback_query() -> Update {
+        update(r#"{"update_id":2,"callback_query":{"id":"4","from":{"id":5,"is_bot":false,"first_name":"Test"},"chat_instance":"6","data":"yes"}}"#)
+    }
+
+    #[test]
+    fn test_dispatch_by_kind() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut dispatcher = Dispatcher::new();
+        let s = seen.clone();
+        dispatcher.add(Handler::message(move |m| s.lock().unwrap().push(format!("message {}", m.message_id))));
+        let s = seen.clone();
+        dispatcher.add(Handler::callback_query(move |q| s.lock().unwrap().push(format!("callback {}", q.id))));
+        assert!(dispatcher.dispatch(&callback_query()));
+        assert!(dispatcher.dispatch(&text_message()));
+        assert!(!dispatcher.dispatch(&update(r#"{"update_id":3}"#)));
+        assert_eq!(*seen.lock().unwrap(), vec!["callback 4", "message 2"]);
+    }
+
+    #[test]
+    fn test_filters_and_order() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut dispatcher = Dispatcher::new();
+        let s = seen.clone();
+        dispatcher.add(Handler::update(move |_| s.lock().unwrap().push("fallback")).order(10));
+        let s = seen.clone();
+        dispatcher.add(Handler::message(move |_| s.lock().unwrap().push("text")));
+        let s = seen.clone();
+        dispatcher.add(Handler::message(move |_| s.lock().unwrap().push("goodbye"))
+            .filter(|m| m.text.is_some())
+            .filter(|m| m.text.as_deref() == Some("goodbye"))
+            .order(-1));
+        dispatcher.dispatch(&text_message());
+        dispatcher.dispatch(&update(r#"{"update_id":3,"message":{"message_id":2,"date":0,"chat":{"id":3,"type":"private"},"text":"goodbye"}}"#));
+        dispatcher.dispatch(&callback_query());
+        assert_eq!(*seen.lock().unwrap(), vec!["text", "goodbye", "fallback"]);
+    }
+
+    #[test]
+    fn test_run() {
+        let seen = Arc::new(Mutex::new(0));
+        let mut dispatcher = Dispatcher::new();
+        let s = seen.clone();
+        dispatcher.add(Handler::message(move |_| *s.lock().unwrap() += 1));
+        let (tx, rx) = mpsc::channel();
+        tx.send(text_message()).unwrap();
+        tx.send(callback_query()).unwrap();
+        tx.send(text_message()).unwrap();
+        drop(tx);
+        dispatcher.run(rx, &AckHandle::default()).unwrap();
+        assert_eq!(*seen.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_run_acknowledges_polled_updates() {
+        let (url, api) = fake_api(vec![
+            r#"{"ok":true,"result":[{"update_id":1,"message":{"message_id":2,"date":0,"chat":{"id":3,"type":"private"},"text":"a"}},{"update_id":2}]}"#,
+            r#"{"ok":true,"result":[{"update_id":3,"message":{"message_id":4,"date":0,"chat":{"id":3,"type":"private"},"text":"b"}}]}"#,
+            r#"{"ok":true,"result":[]}"#,
+        ]);
+        let mut bot = Bot::new("key".to_string()).with_api_url(&url).with_offset_store(MemoryOffsetStore::new());
+        let (tx, rx) = mpsc::channel();
+        let (seen_tx, seen_rx) = mpsc::channel();
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add(Handler::message(move |m| seen_tx.send(m.message_id).unwrap()));
+        let polling = bot.start_polling(tx);
+        let ack = bot.ack_handle();
+        let running = thread::spawn(move || dispatcher.run(rx, &ack));
+        assert_eq!(seen_rx.recv_timeout(Duration::from_secs(5)), Ok(2));
+        assert_eq!(seen_rx.recv_timeout(Duration::from_secs(5)), Ok(4));
+        // without acknowledging, polling would wait for the first batch forever
+        let offsets: Vec<Value> = api.join().unwrap().iter().map(|(_, body)| serde_json::from_slice::<Value>(body).unwrap()["offset"].clone()).collect();
+        assert_eq!(offsets, vec![0, 3, 4]);
+        polling.stop();
+        running.join().unwrap().unwrap();
+    }
+}
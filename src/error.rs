@@ -0,0 +1,95 @@
+//! This module contains the error type returned by all methods of the Bot API</br>
+//! Every method returns a `Result<T, TelegramError>`, so callers can tell apart
+//! network problems, undecodable responses and errors reported by Telegram itself, e.g.:
+//! ```ignore
+//! match bot.get_me() {
+//!     Ok(me) => println!("{}", me),
+//!     Err(TelegramError::Api { error_code, description, .. }) => println!("{}: {}", error_code, description),
+//!     Err(e) => println!("{}", e),
+//! }
+//! ```
+
+extern crate json;
+
+use serde_json::Value;
+use std::fmt;
+use crate::objects::ResponseParameters;
+
+#[derive(Debug)]
+pub enum TelegramError {
+    /// the request could not be sent or the response could not be read
+    Transport(reqwest::Error),
+    /// a file could not be read or written
+    Io(std::io::Error),
+    /// the file is bigger than the Bot API allows to download
+    FileTooLarge(u64),
+    /// the response was not valid JSON or did not have the expected shape
+    Decode(String),
+    /// passport data could not be decrypted or did not match its hash
+    Passport(String),
+    /// Telegram answered with `"ok": false`
+    Api {
+        error_code: i32,
+        description: String,
+        parameters: Option<ResponseParameters>,
+    },
+}
+
+impl TelegramError {
+    pub(crate) fn from_response(res: &Value) -> TelegramError {
+        let parameters = serde_json::from_value(res["parameters"].clone()).ok();
+        TelegramError::Api {
+            error_code: res["error_code"].as_i64().unwrap_or(0) as i32,
+            description: res["description"].as_str().unwrap_or("").to_string(),
+            parameters,
+        }
+    }
+}
+
+impl fmt::Display for TelegramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TelegramError::Transport(e) => write!(f, "transport error: {}", e),
+            TelegramError::Io(e) => write!(f, "io error: {}", e),
+            TelegramError::FileTooLarge(size) => write!(f, "file is too large to download: {} bytes", size),
+            TelegramError::Decode(e) => write!(f, "decode error: {}", e),
+            TelegramError::Passport(e) => write!(f, "passport error: {}", e),
+            TelegramError::Api { error_code, description, .. } => write!(f, "api error {}: {}", error_code, description),
+        }
+    }
+}
+
+impl std::error::Error for TelegramError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TelegramError::Transport(e) => Some(e),
+            TelegramError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TelegramError {
+    fn from(e: reqwest::Error) -> Self {
+        // the request url contains the bot token, so it must not end up in any log
+        TelegramError::Transport(e.without_url())
+    }
+}
+
+impl From<std::io::Error> for TelegramError {
+    fn from(e: std::io::Error) -> Self {
+        TelegramError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TelegramError {
+    fn from(e: serde_json::Error) -> Self {
+        TelegramError::Decode(format!("{}", e))
+    }
+}
+
+impl From<json::Error> for TelegramError {
+    fn from(e: json::Error) -> Self {
+        TelegramError::Decode(format!("{}", e))
+    }
+}
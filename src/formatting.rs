@@ -0,0 +1,251 @@
+//! This module contains helpers to build formatted texts, which are escaped correctly for their parse mode</br>
+//! A `FormattedText` is built from parts and rendered as MarkdownV2, HTML or plain text with entities, e.g.:
+//! ```ignore
+//! let text = FormattedText::new().bold("Price:").text(" 4.99 (incl. tax)");
+//! bot.send(SendMessage::new(chat_id, text.to_markdown_v2()).parse_mode(ParseMode::MarkdownV2))?;
+//! bot.send(SendMessage::new(chat_id, text.to_html()).parse_mode(ParseMode::Html))?;
+//! let (plain, entities) = text.to_entities();
+//! bot.send(SendMessage::new(chat_id, plain).entities(entities))?;
+//! ```
+//! Texts from users can also be escaped directly with `escape_markdown_v2` and `escape_html`.
+
+use crate::objects::*;
+
+/// escape_markdown_v2 escapes all characters with a special meaning in MarkdownV2
+pub fn escape_markdown_v2(text: &str) -> String {
+    escape_with(text, |c| "_*[]()~`>#+-=|{}.!\\".contains(c))
+}
+
+/// escape_markdown_v2_code escapes text inside of `code` and ```pre``` blocks
+pub fn escape_markdown_v2_code(text: &str) -> String {
+    escape_with(text, |c| c == '`' || c == '\\')
+}
+
+/// escape_markdown_v2_url escapes the URL part of an inline link
+pub fn escape_markdown_v2_url(text: &str) -> String {
+    escape_with(text, |c| c == ')' || c == '\\')
+}
+
+fn escape_with<F: Fn(char) -> bool>(text: &str, special: F) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// escape_html escapes all characters with a special meaning in HTML, including quotes for attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c)
+        }
+    }
+    escaped
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Underline(String),
+    Strikethrough(String),
+    Code(String),
+    Pre { code: String, language: Option<String> },
+    Link { text: String, url: String },
+    Mention { text: String, user_id: i64 },
+}
+
+/// FormattedText is a text made of plain and formatted parts
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FormattedText {
+    parts: Vec<Part>,
+}
+
+impl FormattedText {
+    pub fn new() -> FormattedText {
+        FormattedText::default()
+    }
+
+    fn push(mut self, part: Part) -> FormattedText {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn text(self, text: &str) -> FormattedText {
+        self.push(Part::Text(text.to_string()))
+    }
+
+    pub fn bold(self, text: &str) -> FormattedText {
+        self.push(Part::Bold(text.to_string()))
+    }
+
+    pub fn italic(self, text: &str) -> FormattedText {
+        self.push(Part::Italic(text.to_string()))
+    }
+
+    pub fn underline(self, text: &str) -> FormattedText {
+        self.push(Part::Underline(text.to_string()))
+    }
+
+    pub fn strikethrough(self, text: &str) -> FormattedText {
+        self.push(Part::Strikethrough(text.to_string()))
+    }
+
+    /// code adds inline code
+    pub fn code(self, code: &str) -> FormattedText {
+        self.push(Part::Code(code.to_string()))
+    }
+
+    /// pre adds a block of code, optionally highlighted as `language`
+    pub fn pre(self, code: &str, language: Option<&str>) -> FormattedText {
+        self.push(Part::Pre { code: code.to_string(), language: language.map(|l| l.to_string()) })
+    }
+
+    pub fn link(self, text: &str, url: &str) -> FormattedText {
+        self.push(Part::Link { text: text.to_string(), url: url.to_string() })
+    }
+
+    /// mention links to the user with `user_id`, which also works for users without username
+    pub fn mention(self, text: &str, user_id: i64) -> FormattedText {
+        self.push(Part::Mention { text: text.to_string(), user_id })
+    }
+
+    /// to_markdown_v2 renders the text to be sent with `ParseMode::MarkdownV2`
+    pub fn to_markdown_v2(&self) -> String {
+        self.parts.iter().map(|part| match part {
+            Part::Text(t) => escape_markdown_v2(t),
+            Part::Bold(t) => format!("*{}*", escape_markdown_v2(t)),
+            Part::Italic(t) => format!("_{}_", escape_markdown_v2(t)),
+            Part::Underline(t) => format!("__{}__", escape_markdown_v2(t)),
+            Part::Strikethrough(t) => format!("~{}~", escape_markdown_v2(t)),
+            Part::Code(c) => format!("`{}`", escape_markdown_v2_code(c)),
+            Part::Pre { code, language } => format!("```{}\n{}\n```", escape_markdown_v2_code(language.as_deref().unwrap_or("")),
+                                                    escape_markdown_v2_code(code)),
+            Part::Link { text, url } => format!("[{}]({})", escape_markdown_v2(text), escape_markdown_v2_url(url)),
+            Part::Mention { text, user_id } => format!("[{}](tg://user?id={})", escape_markdown_v2(text), user_id),
+        }).collect()
+    }
+
+    /// to_html renders the text to be sent with `ParseMode::Html`
+    pub fn to_html(&self) -> String {
+        self.parts.iter().map(|part| match part {
+            Part::Text(t) => escape_html(t),
+            Part::Bold(t) => format!("<b>{}</b>", escape_html(t)),
+            Part::Italic(t) => format!("<i>{}</i>", escape_html(t)),
+            Part::Underline(t) => format!("<u>{}</u>", escape_html(t)),
+            Part::Strikethrough(t) => format!("<s>{}</s>", escape_html(t)),
+            Part::Code(c) => format!("<code>{}</code>", escape_html(c)),
+            Part::Pre { code, language: Some(l) } => format!("<pre><code class=\"language-{}\">{}</code></pre>", escape_html(l), escape_html(code)),
+            Part::Pre { code, language: None } => format!("<pre>{}</pre>", escape_html(code)),
+            Part::Link { text, url } => format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text)),
+            Part::Mention { text, user_id } => format!("<a href=\"tg://user?id={}\">{}</a>", user_id, escape_html(text)),
+        }).collect()
+    }
+
+    /// to_entities returns the plain text together with the entities to be sent instead of a parse mode,
+    /// offsets and lengths are counted in UTF-16 code units as required by Telegram
+    pub fn to_entities(&self) -> (String, Vec<MessageEntity>) {
+        let mut text = String::new();
+        let mut entities = vec![];
+        for part in &self.parts {
+            let (content, typ) = match part {
+                Part::Text(t) => (t, None),
+                Part::Bold(t) => (t, Some(MessageEntityType::Bold)),
+                Part::Italic(t) => (t, Some(MessageEntityType::Italic)),
+                Part::Underline(t) => (t, Some(MessageEntityType::Underline)),
+                Part::Strikethrough(t) => (t, Some(MessageEntityType::Strikethrough)),
+                Part::Code(c) => (c, Some(MessageEntityType::Code)),
+                Part::Pre { code, .. } => (code, Some(MessageEntityType::Pre)),
+                Part::Link { text, .. } => (text, Some(MessageEntityType::TextLink)),
+                Part::Mention { text, .. } => (text, Some(MessageEntityType::TextMention)),
+            };
+            let offset = text.encode_utf16().count() as i32;
+            text.push_str(content);
+            let typ = match typ {
+                Some(typ) if !content.is_empty() => typ,
+                _ => continue
+            };
+            let mut entity = MessageEntity::empty();
+            entity.typ = typ;
+            entity.offset = offset;
+            entity.length = content.encode_utf16().count() as i32;
+            match part {
+                Part::Pre { language, .. } => entity.language = language.clone(),
+                Part::Link { url, .. } => entity.url = Some(url.clone()),
+                Part::Mention { user_id, .. } => {
+                    let mut user = User::empty();
+                    user.id = *user_id;
+                    entity.user = Some(user);
+                },
+                _ => ()
+            }
+            entities.push(entity);
+        }
+        (text, entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> FormattedText {
+        FormattedText::new()
+            .bold("Price:")
+            .text(" 4.99 (incl. tax) ")
+            .code("a`b")
+            .text(" ")
+            .link("shop_1", "https://example.com/a_(b)")
+            .text(" ")
+            .mention("Ann <3", 1234)
+    }
+
+    #[test]
+    fn test_escape_markdown_v2() {
+        assert_eq!(escape_markdown_v2("_*[]()~`>#+-=|{}.!\\ ok"), "\\_\\*\\[\\]\\(\\)\\~\\`\\>\\#\\+\\-\\=\\|\\{\\}\\.\\!\\\\ ok");
+        assert_eq!(escape_markdown_v2_code("a`b\\c_d"), "a\\`b\\\\c_d");
+        assert_eq!(escape_markdown_v2_url("https://example.com/(x)"), "https://example.com/(x\\)");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<b>\"Tom & Jerry\"</b>"), "&lt;b&gt;&quot;Tom &amp; Jerry&quot;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_to_markdown_v2() {
+        assert_eq!(example().to_markdown_v2(),
+                   "*Price:* 4\\.99 \\(incl\\. tax\\) `a\\`b` [shop\\_1](https://example.com/a_(b\\)) [Ann <3](tg://user?id=1234)");
+        assert_eq!(FormattedText::new().pre("let x = 1;", Some("rust")).to_markdown_v2(), "```rust\nlet x = 1;\n```");
+        assert_eq!(FormattedText::new().pre("x", Some("a`b\\")).to_markdown_v2(), "```a\\`b\\\\\nx\n```");
+    }
+
+    #[test]
+    fn test_to_html() {
+        assert_eq!(example().to_html(),
+                   "<b>Price:</b> 4.99 (incl. tax) <code>a`b</code> <a href=\"https://example.com/a_(b)\">shop_1</a> <a href=\"tg://user?id=1234\">Ann &lt;3</a>");
+        assert_eq!(FormattedText::new().pre("a < b", Some("rust")).to_html(), "<pre><code class=\"language-rust\">a &lt; b</code></pre>");
+    }
+
+    #[test]
+    fn test_to_entities() {
+        let (text, entities) = FormattedText::new().text("\u{1F600} ").bold("hi").text(" ").link("here", "https://example.com").to_entities();
+        assert_eq!(text, "\u{1F600} hi here");
+        assert_eq!(entities.len(), 2);
+        assert!(matches!(entities[0].typ, MessageEntityType::Bold));
+        assert_eq!((entities[0].offset, entities[0].length), (3, 2));
+        assert!(matches!(entities[1].typ, MessageEntityType::TextLink));
+        assert_eq!((entities[1].offset, entities[1].length), (6, 4));
+        assert_eq!(entities[1].url.as_deref(), Some("https://example.com"));
+    }
+}
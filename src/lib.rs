@@ -3,5 +3,19 @@
 //! To learn more about the individual types and methods, please visit <a href="https://core.telegram.org/bots/api">Telegram Bot API</a>.</br>
 //! The issue tracker is located on <a href="https://github.com/jrmbchtl/telegram-bot-rs">Github</a>
 pub mod objects;
-pub mod methods;
+#[macro_use] pub mod methods;
+#[cfg(feature = "async")] pub mod async_bot;
+#[cfg(feature = "passport")] pub mod passport;
+mod request;
+pub mod commands;
+pub mod conversation;
+pub mod dispatcher;
+pub mod error;
+pub mod formatting;
+pub mod offset;
+pub mod polling;
+pub mod rate_limit;
+pub mod retry;
+pub mod webhook;
+#[cfg(test)] mod testing;
 #[macro_use] pub mod api_macros;
\ No newline at end of file
@@ -7,1115 +7,988 @@
 //! ```ignore
 //! bot.get_me();
 //! ```
+//! All methods return a `Result`, see `TelegramError` for the possible errors.</br>
+//! Every method also has a request struct, which only needs the required parameters
+//! and can be sent with `send`, e.g.:
+//! ```ignore
+//! bot.send(SendMessage::new(chat_id, "hello").disable_notification(true))?;
+//! ```
+#![allow(clippy::too_many_arguments)]
 
-extern crate json;
-extern crate rustc_serialize;
-extern crate urlencoding;
-
-use json::JsonValue;
+use serde::Deserialize;
+use serde_json::Value;
 use crate::*;
 use crate::objects::*;
+use crate::error::TelegramError;
+use crate::request::Request;
+use crate::offset::{AckHandle, AckedOffset, OffsetStore};
+use crate::polling::{sleep_while_running, PollingConfig, PollingHandle};
+use crate::rate_limit::RateLimiter;
+use crate::retry::{Retry, RetryPolicy};
+use crate::webhook::WebhookServer;
+use std::net::ToSocketAddrs;
+use reqwest::blocking::multipart;
+use reqwest::header::CONTENT_TYPE;
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::sync::mpsc::Sender;
 use std::thread;
+use std::time::Duration;
 
 pub struct Bot {
     key: String,
+    api_url: String,
     offset: i64,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    offset_store: Option<AckedOffset>,
+    on_decode_error: Option<DecodeErrorHandler>,
+    client: reqwest::blocking::Client,
+    download_timeout: Option<Duration>,
 }
 
-const BASE_URL: &str = "https://api.telegram.org/bot";
-
-impl Bot {
-    pub fn new<'a>(api_key: String) -> Bot {
-        Bot {
-            key: api_key,
-            offset: 0,
-        }
-    }
-
-    /// start_polling is used to get updates from the bot.
-    /// ```ignore
-    /// let (tx, rx) = mpsc::channel();
-    /// bot.start_polling(tx);
-    /// let received = rx.recv().unwrap();
-    /// println!("Got: {}", received);
-    /// ```
-
-    pub fn start_polling(&mut self, tx: Sender<Update>) {
-        let mut bot = self.clone();
-        thread::spawn(move || {
-            loop {
-                let updates = bot.get_updates(None, None, None);
-                match updates {
-                    Some(us) => {
-                        for u in us {
-                            assert!(tx.send(u).is_ok());
-                        }
-                    },
-                    None => ()
-                };
-            }
-        });
+pub(crate) const API_URL: &str = "https://api.telegram.org";
+/// how long to wait for a response, on top of the timeout of a long poll
+pub(crate) const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+/// the Bot API only allows to download files up to 20MB
+pub const MAX_DOWNLOAD_SIZE: u64 = 20 * 1024 * 1024;
+
+/// decode_response turns the raw body of a Bot API response into its `result` field
+/// or the matching `TelegramError`
+pub(crate) fn decode_response(text: &str) -> Result<Value, TelegramError> {
+    let res: Value = serde_json::from_str(text)?;
+    match res["ok"].as_bool() {
+        Some(true) => Ok(res["result"].clone()),
+        Some(false) => Err(TelegramError::from_response(&res)),
+        None => Err(TelegramError::Decode("response is missing the \"ok\" field".to_string()))
     }
+}
 
-    fn send_request(&self, method: String, parameters: String) -> JsonValue {
-        let request = format!("{}{}/{}?{}", BASE_URL, self.key, method, parameters);
-        // println!("{}", request);
-        let res = reqwest::blocking::get(request);
-        let mut json_response = JsonValue::Null;
-        match res {
-            Ok(r) => {
-                match r.text() {
-                    Ok(result) => json_response = json::parse(&*result).unwrap(),
-                    Err(_) => ()
+/// DecodeErrorHandler is called with every received update, which could not be decoded, and the error
+pub type DecodeErrorHandler = Arc<dyn Fn(&Value, &TelegramError) + Send + Sync>;
+
+/// decode_updates decodes the result of getUpdates one update at a time, so an update this crate can't decode
+/// doesn't fail the whole batch. Undecodable updates are passed to `on_decode_error` and skipped in `offset_store`.
+/// It returns the decoded updates and the id of the last received update.
+pub(crate) fn decode_updates(res: Value, offset_store: Option<&AckedOffset>, on_decode_error: Option<&DecodeErrorHandler>)
+                             -> Result<(Vec<Update>, Option<i64>), TelegramError> {
+    let mut updates = vec![];
+    let mut last = None;
+    for value in serde_json::from_value::<Vec<Value>>(res)? {
+        let update_id = value["update_id"].as_i64();
+        match Update::deserialize(&value) {
+            Ok(update) => updates.push(update),
+            Err(e) => {
+                if let (Some(offset_store), Some(update_id)) = (offset_store, update_id) {
+                    offset_store.skip(update_id, last)?;
+                }
+                if let Some(on_decode_error) = on_decode_error {
+                    on_decode_error(&value, &TelegramError::from(e));
                 }
             }
-            Err(_) => ()
         }
-        json_response
+        last = update_id.or(last);
     }
+    Ok((updates, last))
+}
 
-    pub fn get_updates(&mut self, limit: Option<i32>, timeout: Option<i32>, allowed_updates: Option<Vec<String>>) -> Option<Vec<Update>> {
-        let mut parameters = "".to_string();
-        parameters.push_str(&*format!("offset={}&", self.offset));
-        match limit {
-            Some(o) => parameters.push_str(&*format!("limit={}&", Custom::to_json(o))),
-            None => ()
-        }
-        match timeout {
-            Some(o) => parameters.push_str(&*format!("timeout={}&", Custom::to_json(o))),
-            None => ()
-        }
-        match allowed_updates {
-            Some(o) => parameters.push_str(&*format!("allowed_updates={}&", Custom::to_json(o))),
-            None => ()
-        }
-        parameters.pop();
-        let res = self.send_request("getUpdates".to_string(), parameters);
-        let ret: Vec<Update> = Custom::from_json(res["result"].clone());
-        if !res["ok"].as_bool().unwrap() || ret.len() == 0 {
-            None
-        } else {
-            self.offset = ret[ret.len() - 1].clone().update_id + 1;
-            self.send_request("getUpdates".to_string(), format!("offset={}", self.offset));
-            Some(ret)
+/// download_path checks whether `file` can be downloaded and returns its file_path
+pub(crate) fn download_path(file: &File) -> Result<&str, TelegramError> {
+    if let Some(size) = file.file_size {
+        if size as u64 > MAX_DOWNLOAD_SIZE {
+            return Err(TelegramError::FileTooLarge(size as u64));
         }
     }
-
-    pub fn get_me(&mut self) -> User {
-        let res = self.send_request("getMe".to_string(), "".to_string());
-        if !res["ok"].as_bool().unwrap() {
-            User::empty()
-        } else {
-            let ret: User = Custom::from_json(res["result"].clone());
-            ret
-        }
+    match &file.file_path {
+        Some(p) => Ok(p),
+        None => Err(TelegramError::Decode("file has no file_path, use get_file first".to_string()))
     }
+}
 
-    pub fn send_message(&mut self, chat_id: i64, text: String, parse_mode: Option<String>,
-                        entities: Option<Vec<MessageEntity>>, disable_web_page_preview: Option<bool>,
-                        disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
-                        allow_sending_without_reply: Option<bool>, reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                        reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, text
-        }
-        expand_parameters_opt_into_string! {
-            parameters, parse_mode, entities, disable_web_page_preview, disable_notification, reply_to_message_id,
-            allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendMessage".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+/// part_path returns the path a download is written to until it is complete
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_os_string();
+    part.push(".part");
+    PathBuf::from(part)
+}
 
-    pub fn forward_message(&mut self, chat_id: i64, from_chat_id: i64, message_id: i32, disable_notification: Option<bool>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, from_chat_id, message_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, disable_notification
-        }
-        parameters.pop();
-        let res = self.send_request("ForwardMessage".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+/// write_chunk writes one chunk of a download, calling `progress` with the bytes written so far
+/// and the expected total size, and fails as soon as more than MAX_DOWNLOAD_SIZE bytes arrive
+pub(crate) fn write_chunk<W: Write>(writer: &mut W, chunk: &[u8], written: &mut u64, total: Option<u64>,
+                                    progress: &mut Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<(), TelegramError> {
+    *written += chunk.len() as u64;
+    if *written > MAX_DOWNLOAD_SIZE {
+        return Err(TelegramError::FileTooLarge(*written));
     }
-
-    pub fn copy_message(&mut self, chat_id: i64, from_chat_id: i64, message_id: i32, caption: Option<String>,
-                        parse_mode: Option<String>, caption_entities: Option<Vec<MessageEntity>>,
-                        disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
-                        allow_sending_without_reply: Option<bool>, reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                        reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> i32 {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, from_chat_id, message_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, caption, parse_mode, caption_entities, disable_notification, reply_to_message_id,
-            allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("copyMessage".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            0
-        } else {
-            let ret: i32 = Custom::from_json(res["result"].clone());
-            ret
-        }
+    writer.write_all(chunk)?;
+    if let Some(p) = progress.as_mut() {
+        p(*written, total);
     }
+    Ok(())
+}
 
-    pub fn send_photo(&mut self, chat_id: i64, photo: String, caption: Option<String>, parse_mode: Option<String>,
-                      caption_entities: Option<Vec<MessageEntity>>, disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
-                      allow_sending_without_reply: Option<bool>, reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                      reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, photo
-        }
-        expand_parameters_opt_into_string! {
-            parameters, caption, parse_mode, caption_entities, disable_notification, reply_to_message_id,
-            allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendPhoto".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
+/// copy_file copies `reader` into `writer` in chunks
+fn copy_file<R: Read, W: Write>(reader: &mut R, writer: &mut W, total: Option<u64>,
+                                mut progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, TelegramError> {
+    let mut buffer = [0; 8192];
+    let mut written = 0;
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
         }
+        write_chunk(writer, &buffer[..n], &mut written, total, &mut progress)?;
     }
+    writer.flush()?;
+    Ok(written)
+}
 
-    pub fn send_audio(&mut self, chat_id: i64, audio: String, caption: Option<String>, parse_mode: Option<String>,
-                      caption_entities: Option<Vec<MessageEntity>>, duration: Option<i32>, performer: Option<String>,
-                      title: Option<String>, thumb: Option<String>, disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
-                      allow_sending_without_reply: Option<bool>, reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                      reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, audio
-        }
-        expand_parameters_opt_into_string! {
-            parameters, caption, parse_mode, caption_entities, duration, performer, title, thumb, disable_notification, reply_to_message_id,
-            allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendAudio".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+/// TelegramMethod is implemented by all request structs, e.g. `SendMessage`, so they can be sent with `send`
+pub trait TelegramMethod {
+    type Response;
 
-    pub fn send_document(&mut self, chat_id: i64, document: String, thumb: Option<String>, caption: Option<String>,
-                         parse_mode: Option<String>, caption_entities: Option<Vec<MessageEntity>>,
-                         disable_content_type_detection: Option<bool>, disable_notification: Option<bool>,
-                         reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                         reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                         reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, document
-        }
-        expand_parameters_opt_into_string! {
-            parameters, thumb, caption, parse_mode, caption_entities, disable_content_type_detection, disable_notification,
-            reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendDocument".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+    #[doc(hidden)]
+    fn into_request(self) -> Request;
 
-    pub fn send_video(&mut self, chat_id: i64, video: String, duration: Option<i32>, width: Option<i32>,
-                      height: Option<i32>, thumb: Option<String>, caption: Option<String>, parse_mode: Option<String>,
-                      caption_entities: Option<Vec<MessageEntity>>, supports_streaming: Option<bool>,
-                      disable_notification: Option<bool>, reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                      reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                      reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, video
-        }
-        expand_parameters_opt_into_string! {
-            parameters, duration, width, height, thumb, caption, parse_mode, caption_entities, supports_streaming,
-            disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendVideo".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+    #[doc(hidden)]
+    fn decode(res: Value) -> Result<Self::Response, TelegramError>;
+}
 
-    pub fn send_animation(&mut self, chat_id: i64, animation: String, duration: Option<i32>, width: Option<i32>,
-                          height: Option<i32>, thumb: Option<String>, caption: Option<String>, parse_mode: Option<String>,
-                          caption_entities: Option<Vec<MessageEntity>>, disable_notification: Option<bool>,
-                          reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                          reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                          reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, animation
-        }
-        expand_parameters_opt_into_string! {
-            parameters, duration, width, height, thumb, caption, parse_mode, caption_entities,
-            disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendAnimation".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+/// telegram_methods contains all methods, which only send their parameters and decode the result.
+/// It is expanded once for the request structs and once for the blocking `Bot` as well as the `AsyncBot`,
+/// so both share the same method set.
+macro_rules! telegram_methods {
+    ($expand:ident) => {
+        $expand! {
+            fn set_webhook("setWebhook", SetWebhook) -> bool {
+                url: String, certificate: Option<InputFile>, ip_address: Option<String>, max_connections: Option<i32>,
+                allowed_updates: Option<Vec<String>>, drop_pending_updates: Option<bool>
+            }
 
-    pub fn send_voice(&mut self, chat_id: i64, voice: String, caption: Option<String>, parse_mode: Option<String>,
-                      caption_entities: Option<Vec<MessageEntity>>, duration: Option<i32>, disable_notification: Option<bool>,
-                      reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                      reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                      reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, voice
-        }
-        expand_parameters_opt_into_string! {
-            parameters, caption, parse_mode, caption_entities, duration,
-            disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendVoice".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn delete_webhook("deleteWebhook", DeleteWebhook) -> bool {
+                drop_pending_updates: Option<bool>
+            }
 
-    pub fn send_video_note(&mut self, chat_id: i64, video_note: String, duration: Option<i32>, length: Option<i32>,
-                           thumb: Option<String>, disable_notification: Option<bool>,
-                           reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                           reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                           reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, video_note
-        }
-        expand_parameters_opt_into_string! {
-            parameters, duration, length, thumb, disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendVideoNote".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn get_webhook_info("getWebhookInfo", GetWebhookInfo) -> WebhookInfo {}
 
-    pub fn send_media_group(&mut self, chat_id: i64, media: Vec<InputMedia>, disable_notification: Option<bool>,
-                            reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>) -> Option<Vec<Message>> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, media
-        }
-        expand_parameters_opt_into_string! {
-            parameters, disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        parameters.pop();
-        let res = self.send_request("sendMediaGroup".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Vec<Message> = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn get_me("getMe", GetMe) -> User {}
 
-    pub fn send_location(&mut self, chat_id: i64, latitude: f64, longitude: f64, horizontal_accuracy: Option<f64>,
-                         live_period: Option<i32>, heading: Option<i32>, proximity_alert_radius: Option<i32>, disable_notification: Option<bool>,
-                         reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                         reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>, reply_markup_rkr: Option<ReplyKeyboardRemove>,
-                         reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, latitude, longitude
-        }
-        expand_parameters_opt_into_string! {
-            parameters, horizontal_accuracy, live_period, heading, proximity_alert_radius, disable_notification,
-            reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendLocation".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn send_message("sendMessage", SendMessage) -> Message {
+                chat_id: ChatId, text: String, parse_mode: Option<ParseMode>, entities: Option<Vec<MessageEntity>>,
+                disable_web_page_preview: Option<bool>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn edit_message_live_location(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                                      latitude: f64, longitude: f64, horizontal_accuracy: Option<f64>,
-                                      heading: Option<i32>, proximity_alert_radius: Option<i32>,
-                                      reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, latitude, longitude
-        }
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id, horizontal_accuracy, heading, proximity_alert_radius
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("editMessageLiveLocation".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn forward_message("forwardMessage", ForwardMessage) -> Message {
+                chat_id: ChatId, from_chat_id: ChatId, message_id: i32, disable_notification: Option<bool>
+            }
 
-    pub fn stop_message_live_location(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                                      reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("stopMessageLiveLocation".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn copy_message("copyMessage", CopyMessage) -> MessageId {
+                chat_id: ChatId, from_chat_id: ChatId, message_id: i32, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn send_venue(&mut self, chat_id: i64, latitude: f64, longitude: f64, title: String, address: String,
-                      foursquare_id: Option<String>, foursquare_type: Option<String>, google_place_id: Option<String>,
-                      google_place_type: Option<String>, disable_notification: Option<bool>,
-                      reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                      reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                      reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, latitude, longitude, title, address
-        }
-        expand_parameters_opt_into_string! {
-            parameters, foursquare_id, foursquare_type, google_place_id, google_place_type, disable_notification,
-            reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendVenue".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn send_photo("sendPhoto", SendPhoto) -> Message {
+                chat_id: ChatId, photo: InputFile, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn send_contact(&mut self, chat_id: i64, phone_number: String, first_name: String,
-                        last_name: Option<String>, vcard: Option<String>, disable_notification: Option<bool>,
-                        reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                        reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                        reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, phone_number, first_name
-        }
-        expand_parameters_opt_into_string! {
-            parameters, last_name, vcard, disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendContact".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn send_audio("sendAudio", SendAudio) -> Message {
+                chat_id: ChatId, audio: InputFile, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, duration: Option<i32>, performer: Option<String>,
+                title: Option<String>, thumb: Option<InputFile>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn send_poll(&mut self, chat_id: i64, question: String, options: Vec<String>,
-                     is_anonymous: Option<bool>, typ: Option<String>, allows_multiple_answers: Option<bool>,
-                     correct_option_id: Option<i32>, explanation: Option<String>, explanation_parse_mode: Option<String>,
-                     explanation_entities: Option<Vec<MessageEntity>>, open_period: Option<i32>, close_date: Option<i32>,
-                     is_closed: Option<i32>, disable_notification: Option<bool>,
-                     reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                     reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                     reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, question, options
-        }
-        expand_parameters_opt_into_string! {
-            parameters, is_anonymous, typ, allows_multiple_answers, correct_option_id, explanation,
-            explanation_parse_mode, explanation_entities, open_period, close_date, is_closed,
-            disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendPoll".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn send_document("sendDocument", SendDocument) -> Message {
+                chat_id: ChatId, document: InputFile, thumb: Option<InputFile>, caption: Option<String>,
+                parse_mode: Option<ParseMode>, caption_entities: Option<Vec<MessageEntity>>,
+                disable_content_type_detection: Option<bool>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn send_dice(&mut self, chat_id: i64, emoji: Option<String>, disable_notification: Option<bool>,
-                     reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                     reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                     reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, emoji, disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendDice".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
-    }
+            fn send_video("sendVideo", SendVideo) -> Message {
+                chat_id: ChatId, video: InputFile, duration: Option<i32>, width: Option<i32>, height: Option<i32>,
+                thumb: Option<InputFile>, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, supports_streaming: Option<bool>,
+                disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn send_chat_action(&mut self, chat_id: i64, action: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, action
-        }
-        parameters.pop();
-        let res = self.send_request("sendChatAction".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn send_animation("sendAnimation", SendAnimation) -> Message {
+                chat_id: ChatId, animation: InputFile, duration: Option<i32>, width: Option<i32>, height: Option<i32>,
+                thumb: Option<InputFile>, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn get_user_profile_photos(&mut self, user_id: i64, offset: Option<i32>, limit: Option<i32>) -> Option<UserProfilePhotos> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, user_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, offset, limit
-        }
-        parameters.pop();
-        let res = self.send_request("getUserProfilePhotos".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: UserProfilePhotos = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn send_voice("sendVoice", SendVoice) -> Message {
+                chat_id: ChatId, voice: InputFile, caption: Option<String>, parse_mode: Option<ParseMode>,
+                caption_entities: Option<Vec<MessageEntity>>, duration: Option<i32>,
+                disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn kick_chat_member(&mut self, chat_id: i64, user_id: i64, until_date: Option<i32>, revoke_messages: Option<bool>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, until_date, revoke_messages
-        }
-        parameters.pop();
-        let res = self.send_request("kickChatMember".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn send_video_note("sendVideoNote", SendVideoNote) -> Message {
+                chat_id: ChatId, video_note: InputFile, duration: Option<i32>, length: Option<i32>,
+                thumb: Option<InputFile>, disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn unban_chat_member(&mut self, chat_id: i64, user_id: i64, only_if_banned: Option<bool>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, only_if_banned
-        }
-        parameters.pop();
-        let res = self.send_request("unbanChatMember".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            /// send_media_group uploads all media given as path or in-memory bytes via `attach://` references
+            fn send_media_group("sendMediaGroup", SendMediaGroup) -> Vec<Message> {
+                chat_id: ChatId, media: Vec<InputMedia>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>
+            }
 
-    pub fn restrict_chat_member(&mut self, chat_id: i64, user_id: i64, permissions: ChatPermissions, until_date: Option<i32>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id, permissions
-        }
-        expand_parameters_opt_into_string! {
-            parameters, until_date
-        }
-        parameters.pop();
-        let res = self.send_request("restrictChatMember".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn send_location("sendLocation", SendLocation) -> Message {
+                chat_id: ChatId, latitude: f64, longitude: f64, horizontal_accuracy: Option<f64>,
+                live_period: Option<i32>, heading: Option<i32>, proximity_alert_radius: Option<i32>,
+                disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn promote_chat_member(&mut self, chat_id: i64, user_id: i64, is_anonymous: Option<bool>,
-                               can_manage_chat: Option<bool>, can_post_messages: Option<bool>,
-                               can_edit_messages: Option<bool>, can_delete_messages: Option<bool>,
-                               can_manage_voice_chats: Option<bool>, can_restrict_members: Option<bool>,
-                               can_promote_members: Option<bool>, can_change_info: Option<bool>,
-                               can_invite_users: Option<bool>, can_pin_messages: Option<bool>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, is_anonymous, can_manage_chat, can_post_messages, can_edit_messages,
-            can_delete_messages, can_manage_voice_chats, can_restrict_members, can_promote_members,
-            can_change_info, can_invite_users, can_pin_messages
-        }
-        parameters.pop();
-        let res = self.send_request("promoteChatMember".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn edit_message_live_location("editMessageLiveLocation", EditMessageLiveLocation) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>, latitude: f64,
+                longitude: f64, horizontal_accuracy: Option<f64>, heading: Option<i32>,
+                proximity_alert_radius: Option<i32>, reply_markup: Option<InlineKeyboardMarkup>
+            }
 
-    pub fn set_chat_administrator_custom_title(&mut self, chat_id: i64, user_id: i64, custom_title: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id, custom_title
-        }
-        parameters.pop();
-        let res = self.send_request("setChatAdministratorCustomTitle".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn stop_message_live_location("stopMessageLiveLocation", StopMessageLiveLocation) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>,
+                reply_markup: Option<InlineKeyboardMarkup>
+            }
 
-    pub fn set_chat_permissions(&mut self, chat_id: i64, permissions: ChatPermissions) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, permissions
-        }
-        parameters.pop();
-        let res = self.send_request("setChatPermissions".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn send_venue("sendVenue", SendVenue) -> Message {
+                chat_id: ChatId, latitude: f64, longitude: f64, title: String, address: String,
+                foursquare_id: Option<String>, foursquare_type: Option<String>, google_place_id: Option<String>,
+                google_place_type: Option<String>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn export_chat_invite_link(&mut self, chat_id: i64) -> Option<String> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("exportChatInviteLink".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: String = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn send_contact("sendContact", SendContact) -> Message {
+                chat_id: ChatId, phone_number: String, first_name: String, last_name: Option<String>,
+                vcard: Option<String>, disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn create_chat_invite_link(&mut self, chat_id: i64, expire_date: Option<i32>, member_limit: Option<i32>) -> Option<ChatInviteLink> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, expire_date, member_limit
-        }
-        parameters.pop();
-        let res = self.send_request("createChatInviteLink".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: ChatInviteLink = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn send_poll("sendPoll", SendPoll) -> Message {
+                chat_id: ChatId, question: String, options: Vec<String>, is_anonymous: Option<bool>, typ: Option<PollType>,
+                allows_multiple_answers: Option<bool>, correct_option_id: Option<i32>, explanation: Option<String>,
+                explanation_parse_mode: Option<ParseMode>, explanation_entities: Option<Vec<MessageEntity>>,
+                open_period: Option<i32>, close_date: Option<i32>, is_closed: Option<i32>,
+                disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn edit_chat_invite_link(&mut self, chat_id: i64, invite_link: String, expire_date: Option<i32>, member_limit: Option<i32>) -> Option<ChatInviteLink> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, invite_link
-        }
-        expand_parameters_opt_into_string! {
-            parameters, expire_date, member_limit
-        }
-        parameters.pop();
-        let res = self.send_request("editChatInviteLink".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: ChatInviteLink = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn send_dice("sendDice", SendDice) -> Message {
+                chat_id: ChatId, emoji: Option<DiceEmoji>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<ReplyMarkup>
+            }
 
-    pub fn revoke_chat_invite_link(&mut self, chat_id: i64, invite_link: String) -> Option<ChatInviteLink> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, invite_link
-        }
-        parameters.pop();
-        let res = self.send_request("revokeChatInviteLink".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: ChatInviteLink = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn send_chat_action("sendChatAction", SendChatAction) -> bool {
+                chat_id: ChatId, action: ChatAction
+            }
 
-    pub fn delete_chat_photo(&mut self, chat_id: i64) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("deleteChatPhoto".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn get_user_profile_photos("getUserProfilePhotos", GetUserProfilePhotos) -> UserProfilePhotos {
+                user_id: i64, offset: Option<i32>, limit: Option<i32>
+            }
 
-    pub fn set_chat_title(&mut self, chat_id: i64, title: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, title
-        }
-        parameters.pop();
-        let res = self.send_request("setChatTitle".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn get_file("getFile", GetFile) -> File {
+                file_id: String
+            }
 
-    pub fn set_chat_description(&mut self, chat_id: i64, description: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, description
-        }
-        parameters.pop();
-        let res = self.send_request("setChatDescription".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn kick_chat_member("kickChatMember", KickChatMember) -> bool {
+                chat_id: ChatId, user_id: i64, until_date: Option<i32>, revoke_messages: Option<bool>
+            }
 
-    pub fn pin_chat_message(&mut self, chat_id: i64, message_id: i32, disable_notification: Option<bool>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, message_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, disable_notification
-        }
-        parameters.pop();
-        let res = self.send_request("pinChatMessage".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn unban_chat_member("unbanChatMember", UnbanChatMember) -> bool {
+                chat_id: ChatId, user_id: i64, only_if_banned: Option<bool>
+            }
 
-    pub fn unpin_chat_message(&mut self, chat_id: i64, message_id: i32) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, message_id
-        }
-        parameters.pop();
-        let res = self.send_request("unpinChatMessage".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn restrict_chat_member("restrictChatMember", RestrictChatMember) -> bool {
+                chat_id: ChatId, user_id: i64, permissions: ChatPermissions, until_date: Option<i32>
+            }
 
-    pub fn unpin_all_chat_message(&mut self, chat_id: i64) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("unpinAllChatMessages".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn promote_chat_member("promoteChatMember", PromoteChatMember) -> bool {
+                chat_id: ChatId, user_id: i64, is_anonymous: Option<bool>, can_manage_chat: Option<bool>,
+                can_post_messages: Option<bool>, can_edit_messages: Option<bool>, can_delete_messages: Option<bool>,
+                can_manage_voice_chats: Option<bool>, can_restrict_members: Option<bool>,
+                can_promote_members: Option<bool>, can_change_info: Option<bool>, can_invite_users: Option<bool>,
+                can_pin_messages: Option<bool>
+            }
 
-    pub fn leave_chat(&mut self, chat_id: i64) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("leaveChat".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn set_chat_administrator_custom_title("setChatAdministratorCustomTitle", SetChatAdministratorCustomTitle) -> bool {
+                chat_id: ChatId, user_id: i64, custom_title: String
+            }
 
-    pub fn get_chat(&mut self, chat_id: i64) -> Option<Chat> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("getChat".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Chat = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn set_chat_permissions("setChatPermissions", SetChatPermissions) -> bool {
+                chat_id: ChatId, permissions: ChatPermissions
+            }
 
-    pub fn get_chat_administrators(&mut self, chat_id: i64) -> Option<Vec<ChatMember>> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("getChatAdministrators".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Vec<ChatMember> = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn export_chat_invite_link("exportChatInviteLink", ExportChatInviteLink) -> String {
+                chat_id: ChatId
+            }
 
-    pub fn get_chat_chat_members_count(&mut self, chat_id: i64) -> Option<i32> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("getChatMembersCount".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: i32 = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn create_chat_invite_link("createChatInviteLink", CreateChatInviteLink) -> ChatInviteLink {
+                chat_id: ChatId, expire_date: Option<i32>, member_limit: Option<i32>
+            }
 
-    pub fn get_chat_chat_member(&mut self, chat_id: i64, user_id: i64) -> Option<ChatMember> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, user_id
-        }
-        parameters.pop();
-        let res = self.send_request("getChatMember".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: ChatMember = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
-    }
+            fn edit_chat_invite_link("editChatInviteLink", EditChatInviteLink) -> ChatInviteLink {
+                chat_id: ChatId, invite_link: String, expire_date: Option<i32>, member_limit: Option<i32>
+            }
 
-    pub fn set_chat_sticker_set(&mut self, chat_id: i64, sticker_set_name: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, sticker_set_name
-        }
-        parameters.pop();
-        let res = self.send_request("setChatStickerSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
-    }
+            fn revoke_chat_invite_link("revokeChatInviteLink", RevokeChatInviteLink) -> ChatInviteLink {
+                chat_id: ChatId, invite_link: String
+            }
 
-    pub fn delete_chat_sticker_set(&mut self, chat_id: i64) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id
-        }
-        parameters.pop();
-        let res = self.send_request("deleteChatStickerSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
+            fn set_chat_photo("setChatPhoto", SetChatPhoto) -> bool {
+                chat_id: ChatId, photo: InputFile
+            }
+
+            fn delete_chat_photo("deleteChatPhoto", DeleteChatPhoto) -> bool {
+                chat_id: ChatId
+            }
+
+            fn set_chat_title("setChatTitle", SetChatTitle) -> bool {
+                chat_id: ChatId, title: String
+            }
+
+            fn set_chat_description("setChatDescription", SetChatDescription) -> bool {
+                chat_id: ChatId, description: String
+            }
+
+            fn pin_chat_message("pinChatMessage", PinChatMessage) -> bool {
+                chat_id: ChatId, message_id: i32, disable_notification: Option<bool>
+            }
+
+            fn unpin_chat_message("unpinChatMessage", UnpinChatMessage) -> bool {
+                chat_id: ChatId, message_id: i32
+            }
+
+            fn unpin_all_chat_message("unpinAllChatMessages", UnpinAllChatMessages) -> bool {
+                chat_id: ChatId
+            }
+
+            fn leave_chat("leaveChat", LeaveChat) -> bool {
+                chat_id: ChatId
+            }
+
+            fn get_chat("getChat", GetChat) -> Chat {
+                chat_id: ChatId
+            }
+
+            fn get_chat_administrators("getChatAdministrators", GetChatAdministrators) -> Vec<ChatMember> {
+                chat_id: ChatId
+            }
+
+            fn get_chat_chat_members_count("getChatMembersCount", GetChatMembersCount) -> i32 {
+                chat_id: ChatId
+            }
+
+            fn get_chat_chat_member("getChatMember", GetChatMember) -> ChatMember {
+                chat_id: ChatId, user_id: i64
+            }
+
+            fn set_chat_sticker_set("setChatStickerSet", SetChatStickerSet) -> bool {
+                chat_id: ChatId, sticker_set_name: String
+            }
+
+            fn delete_chat_sticker_set("deleteChatStickerSet", DeleteChatStickerSet) -> bool {
+                chat_id: ChatId
+            }
+
+            fn answer_callback_query("answerCallbackQuery", AnswerCallbackQuery) -> bool {
+                callback_query_id: String, text: String, show_alert: Option<bool>, url: Option<String>,
+                cache_time: Option<i32>
+            }
+
+            fn set_my_commands("setMyCommands", SetMyCommands) -> bool {
+                commands: Vec<BotCommand>
+            }
+
+            fn get_my_commands("getMyCommands", GetMyCommands) -> Vec<BotCommand> {}
+
+            fn edit_message_text("editMessageText", EditMessageText) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>, text: Option<String>,
+                parse_mode: Option<ParseMode>, entities: Option<Vec<MessageEntity>>,
+                disable_web_page_preview: Option<bool>, reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn edit_message_caption("editMessageCaption", EditMessageCaption) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>,
+                caption: Option<String>, parse_mode: Option<ParseMode>, caption_entities: Option<Vec<MessageEntity>>,
+                reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn edit_message_media("editMessageMedia", EditMessageMedia) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>,
+                media: Option<InputMedia>, reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn edit_message_reply_markup("editMessageReplyMarkup", EditMessageReplyMarkup) -> MessageOrTrue {
+                chat_id: Option<ChatId>, message_id: Option<i32>, inline_message_id: Option<String>,
+                reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn stop_poll("stopPoll", StopPoll) -> Poll {
+                chat_id: ChatId, message_id: i32, reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn delete_message("deleteMessage", DeleteMessage) -> bool {
+                chat_id: ChatId, message_id: i32
+            }
+
+            fn send_sticker("sendSticker", SendSticker) -> Message {
+                chat_id: ChatId, sticker: InputFile, disable_notification: Option<bool>, reply_to_message_id: Option<i32>,
+                allow_sending_without_reply: Option<bool>, reply_markup: Option<ReplyMarkup>
+            }
+
+            fn get_sticker_set("getStickerSet", GetStickerSet) -> StickerSet {
+                name: String
+            }
+
+            fn upload_sticker_file("uploadStickerFile", UploadStickerFile) -> File {
+                user_id: i64, png_sticker: InputFile
+            }
+
+            fn create_new_sticker_set("createNewStickerSet", CreateNewStickerSet) -> bool {
+                user_id: i64, name: String, title: String, png_sticker: Option<InputFile>, emojis: String,
+                contains_masks: Option<bool>, mask_position: Option<MaskPosition>
+            }
+
+            fn add_sticker_to_set("addStickerToSet", AddStickerToSet) -> bool {
+                user_id: i64, name: String, png_sticker: Option<InputFile>, emojis: String,
+                mask_position: Option<MaskPosition>
+            }
+
+            fn add_sticker_position_in_set("setStickerPositionInSet", SetStickerPositionInSet) -> bool {
+                sticker: String, position: i32
+            }
+
+            fn delete_sticker_from_chat("deleteStickerFromSet", DeleteStickerFromSet) -> bool {
+                sticker: String
+            }
+
+            fn set_sticker_thumb("setStickerSetThumb", SetStickerSetThumb) -> bool {
+                name: String, user_id: i64, thumb: Option<InputFile>
+            }
+
+            fn answer_inline_query("answerInlineQuery", AnswerInlineQuery) -> bool {
+                inline_query_id: String, results: Vec<InlineQueryResult>, cache_time: Option<i32>,
+                is_personal: Option<bool>, next_offset: Option<String>, switch_pm_text: Option<String>,
+                switch_pm_parameter: Option<String>
+            }
+
+            fn send_invoice("sendInvoice", SendInvoice) -> Message {
+                chat_id: ChatId, title: String, description: String, payload: String, provider_token: String,
+                currency: String, prices: Vec<LabeledPrice>, max_tip_amount: Option<i32>,
+                suggested_tip_amounts: Option<Vec<i32>>, start_parameter: Option<String>, provider_data: Option<String>,
+                photo_url: Option<String>, photo_size: Option<i32>, photo_width: Option<i32>, photo_height: Option<i32>,
+                need_name: Option<bool>, need_phone_number: Option<bool>, need_email: Option<bool>,
+                need_shipping_address: Option<bool>, send_phone_number_to_provider: Option<bool>,
+                send_email_to_provider: Option<bool>, is_flexible: Option<bool>, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn answer_shipping_query("answerShippingQuery", AnswerShippingQuery) -> bool {
+                shipping_query_id: String, ok: bool, shipping_options: Option<Vec<ShippingOption>>,
+                error_message: Option<String>
+            }
+
+            fn answer_pre_checkout_query("answerPreCheckoutQuery", AnswerPreCheckoutQuery) -> bool {
+                pre_checkout_query_id: String, ok: bool, error_message: Option<String>
+            }
+
+            fn set_passport_data_errors("setPassportDataErrors", SetPassportDataErrors) -> bool {
+                user_id: i64, errors: Vec<PassportElementError>
+            }
+
+            fn send_game("sendGame", SendGame) -> Message {
+                chat_id: i64, game_short_name: String, disable_notification: Option<bool>,
+                reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
+                reply_markup: Option<InlineKeyboardMarkup>
+            }
+
+            fn set_game_score("setGameScore", SetGameScore) -> MessageOrTrue {
+                user_id: i64, score: i32, force: Option<bool>, disable_edit_message: Option<bool>,
+                chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>
+            }
+
+            fn get_game_high_scores("getGameHighScores", GetGameHighScores) -> Vec<GameHighScore> {
+                user_id: i64, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>
+            }
         }
     }
+}
 
-    pub fn answer_callback_query(&mut self, callback_query_id: String, text: String, show_alert: Option<bool>,
-                                 url: Option<String>, cache_time: Option<i32>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, callback_query_id, text
-        }
-        expand_parameters_opt_into_string! {
-            parameters, show_alert, url, cache_time
-        }
-        parameters.pop();
-        let res = self.send_request("answerCallbackQuery".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
+impl Bot {
+    pub fn new(api_key: String) -> Bot {
+        Bot {
+            key: api_key,
+            api_url: API_URL.to_string(),
+            offset: 0,
+            retry_policy: None,
+            rate_limiter: None,
+            offset_store: None,
+            on_decode_error: None,
+            client: reqwest::blocking::Client::builder()
+                .connect_timeout(HTTP_TIMEOUT)
+                .timeout(None)
+                .build()
+                .expect("the HTTP client could not be initialized"),
+            download_timeout: None,
         }
     }
 
-    pub fn set_my_commands(&mut self, commands: Vec<BotCommand>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, commands
-        }
-        parameters.pop();
-        let res = self.send_request("setMyCommands".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
+    /// with_api_url lets the bot talk to another server than api.telegram.org, e.g. a local Bot API server
+    pub fn with_api_url(mut self, api_url: &str) -> Bot {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
     }
 
-    pub fn get_my_commands(&mut self) -> Option<Vec<BotCommand>> {
-        let res = self.send_request("getMyCommands".to_string(), "".to_string());
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Vec<BotCommand> = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
+    /// with_retry_policy lets the bot wait for `retry_after` and follow chat migrations on its own
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Bot {
+        self.retry_policy = Some(retry_policy);
+        self
     }
 
-    pub fn edit_message_text(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                             text: Option<String>, parse_mode: Option<String>, entities: Option<Vec<MessageEntity>>,
-                             disable_web_page_preview: Option<bool>, reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id, text, parse_mode, entities, disable_web_page_preview
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("editMessageText".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+    /// with_rate_limiter queues all calls sending or editing messages to stay within the limits of `rate_limiter`
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Bot {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+        self
     }
 
-    pub fn edit_message_caption(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                             caption: Option<String>, parse_mode: Option<String>, caption_entities: Option<Vec<MessageEntity>>,
-                             reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id, caption, parse_mode, caption_entities
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("editMessageCaption".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+    /// with_download_timeout limits how long downloading a file may take in total,
+    /// by default a download may take as long as it needs
+    pub fn with_download_timeout(mut self, download_timeout: Duration) -> Bot {
+        self.download_timeout = Some(download_timeout);
+        self
     }
 
-    pub fn edit_message_media(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                                media: Option<InputMedia>, reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id, media
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("editMessageMedia".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+    /// with_offset_store keeps the polling offset in `offset_store`, see the `offset` module.
+    /// Updates have to be acknowledged with `ack` afterwards, otherwise they are received again.
+    pub fn with_offset_store<S: OffsetStore + 'static>(mut self, offset_store: S) -> Bot {
+        self.offset_store = Some(AckedOffset::new(offset_store));
+        self
     }
 
-    pub fn edit_message_reply_markup(&mut self, chat_id: Option<i64>, message_id: Option<i32>, inline_message_id: Option<String>,
-                              reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_opt_into_string! {
-            parameters, chat_id, message_id, inline_message_id
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("editMessageReplyMarkup".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+    /// ack commits the offset after `update` to the offset store, it does nothing without offset store
+    pub fn ack(&self, update: &Update) -> Result<(), TelegramError> {
+        self.ack_handle().ack(update)
     }
 
-    pub fn stop_poll(&mut self, chat_id: i64, message_id: i32, reply_markup_ikm: Option<InlineKeyboardMarkup>) -> Option<Poll> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, message_id
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm
-        }
-        parameters.pop();
-        let res = self.send_request("stopPoll".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
-            None
-        } else {
-            let ret: Poll = Custom::from_json(res["result"].clone());
-            Some(ret)
-        }
+    /// ack_handle returns a handle acknowledging updates for this bot, e.g. for `Dispatcher::run`
+    pub fn ack_handle(&self) -> AckHandle {
+        AckHandle::new(self.offset_store.clone())
     }
 
-    pub fn delete_message(&mut self, chat_id: i64, message_id: i32) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, message_id
-        }
-        parameters.pop();
-        let res = self.send_request("deleteMessage".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
+    /// with_decode_error_handler calls `handler` with every received update, which could not be decoded.
+    /// Such updates are skipped, by default without any notice.
+    pub fn with_decode_error_handler<F: Fn(&Value, &TelegramError) + Send + Sync + 'static>(mut self, handler: F) -> Bot {
+        self.on_decode_error = Some(Arc::new(handler));
+        self
     }
 
-    pub fn send_sticker(&mut self, chat_id: i64, sticker: String, disable_notification: Option<bool>,
-                        reply_to_message_id: Option<i32>, allow_sending_without_reply: Option<bool>,
-                        reply_markup_ikm: Option<InlineKeyboardMarkup>, reply_markup_rkm: Option<ReplyKeyboardMarkup>,
-                        reply_markup_rkr: Option<ReplyKeyboardRemove>, reply_markup_fr: Option<ForceReply>) -> Option<Message> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, chat_id, sticker
-        }
-        expand_parameters_opt_into_string! {
-            parameters, disable_notification, reply_to_message_id, allow_sending_without_reply
-        }
-        expand_parameters_reply_markup_into_string! {
-            parameters, reply_markup_ikm, reply_markup_rkm, reply_markup_rkr, reply_markup_fr
-        }
-        parameters.pop();
-        let res = self.send_request("sendSticker".to_string(), parameters);
-        expand_make_request_to_message! {
-            res
-        }
+    /// start_polling is used to get updates from the bot, using long polling with the default `PollingConfig`.
+    /// Errors while fetching updates are skipped, polling continues after a backoff.
+    /// Errors which make polling impossible stop it, see the `polling` module.
+    /// ```ignore
+    /// let (tx, rx) = mpsc::channel();
+    /// let polling = bot.start_polling(tx);
+    /// let received = rx.recv().unwrap();
+    /// println!("Got: {}", received);
+    /// polling.stop();
+    /// ```
+    pub fn start_polling(&mut self, tx: Sender<Update>) -> PollingHandle {
+        self.start_polling_with_config(PollingConfig::default(), tx)
     }
 
-    pub fn get_sticker_set(&mut self, name: String) -> Option<StickerSet> {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, name
-        }
-        parameters.pop();
-        let res = self.send_request("getStickerSet".to_string(), parameters);
-        if !res["ok"].as_bool().unwrap() {
+    /// start_polling_with_config is like start_polling, but with custom timeout, limit, allowed_updates, backoff and error handler
+    pub fn start_polling_with_config(&mut self, config: PollingConfig, tx: Sender<Update>) -> PollingHandle {
+        let mut bot = self.clone();
+        let running = Arc::new(AtomicBool::new(true));
+        let still_running = running.clone();
+        let thread = thread::spawn(move || {
+            let mut failures = 0;
+            while still_running.load(Ordering::SeqCst) {
+                let updates = match bot.get_updates(config.limit, Some(config.timeout), config.allowed_updates.clone()) {
+                    Ok(updates) => updates,
+                    Err(error) => {
+                        if config.report(&error) {
+                            still_running.store(false, Ordering::SeqCst);
+                            return Some(error);
+                        }
+                        failures += 1;
+                        sleep_while_running(config.backoff(failures), &still_running);
+                        continue;
+                    }
+                };
+                failures = 0;
+                let next = updates.last().map(|u| u.update_id + 1);
+                for u in updates {
+                    if tx.send(u).is_err() {
+                        still_running.store(false, Ordering::SeqCst);
+                        return None;
+                    }
+                }
+                // with an offset store, the same updates would be received again until they are acknowledged
+                if let (Some(offset_store), Some(next)) = (&bot.offset_store, next) {
+                    offset_store.wait_for(next, &still_running);
+                }
+            }
             None
-        } else {
-            let ret: StickerSet = Custom::from_json(res["result"].clone());
-            Some(ret)
+        });
+        PollingHandle::new(running, thread)
+    }
+
+    /// send sends any request struct and returns the decoded result
+    pub fn send<M: TelegramMethod>(&self, method: M) -> Result<M::Response, TelegramError> {
+        let res = self.send_with_retries(method.into_request())?;
+        M::decode(res)
+    }
+
+    /// send_with_retries sends `request` again as long as the retry policy allows it
+    fn send_with_retries(&self, mut request: Request) -> Result<Value, TelegramError> {
+        let policy = match &self.retry_policy {
+            Some(p) => p,
+            None => return self.send_request(request)
+        };
+        let mut attempts = 1;
+        let mut waited = Duration::from_secs(0);
+        loop {
+            let error = match self.send_request(request.clone()) {
+                Err(e) => e,
+                res => return res
+            };
+            match policy.next(&error, attempts, waited) {
+                Some(Retry::After(wait)) => {
+                    thread::sleep(wait);
+                    waited += wait;
+                },
+                Some(Retry::Migrate(chat_id)) if request.migrate(chat_id) => {},
+                _ => return Err(error)
+            }
+            attempts += 1;
         }
     }
 
-    pub fn create_new_sticker_set(&mut self, user_id: i64, name: String, title: String, png_sticker: Option<String>,
-                                  emojis: String, contains_masks: Option<bool>, mask_position: Option<MaskPosition>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, user_id, name, title, emojis
-        }
-        expand_parameters_opt_into_string! {
-            parameters, png_sticker, contains_masks, mask_position
-        }
-        parameters.pop();
-        let res = self.send_request("createNewStickerSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
+    fn send_request(&self, request: Request) -> Result<Value, TelegramError> {
+        if let Some(limiter) = &self.rate_limiter {
+            thread::sleep(limiter.wait_time(&request));
         }
+        let url = request.url(&self.api_url, &self.key);
+        let timeout = request.long_poll_timeout().unwrap_or_default() + HTTP_TIMEOUT;
+        let text = if request.files.is_empty() {
+            self.client.post(url).header(CONTENT_TYPE, "application/json").body(request.body())
+                .timeout(timeout).send()?.text()?
+        } else {
+            let mut form = multipart::Form::new();
+            for (name, value) in request.form_fields() {
+                form = form.text(name, value);
+            }
+            for (name, file) in request.files {
+                form = match file {
+                    InputFile::Path(path) => form.file(name, path)?,
+                    InputFile::Memory { file_name, data } => form.part(name, multipart::Part::bytes(data).file_name(file_name)),
+                    other => form.text(name, format!("{}", other))
+                };
+            }
+            self.client.post(url).multipart(form).timeout(timeout).send()?.text()?
+        };
+        decode_response(&text)
     }
 
-    pub fn add_sticker_to_set(&mut self, user_id: i64, name: String, png_sticker: Option<String>,
-                                  emojis: String, mask_position: Option<MaskPosition>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, user_id, name, emojis
-        }
-        expand_parameters_opt_into_string! {
-            parameters, png_sticker, mask_position
+    /// get_updates returns the updates after the last received one,
+    /// with an offset store after the last acknowledged one instead
+    pub fn get_updates(&mut self, limit: Option<i32>, timeout: Option<i32>, allowed_updates: Option<Vec<String>>) -> Result<Vec<Update>, TelegramError> {
+        let mut request = Request::new("getUpdates");
+        match &self.offset_store {
+            Some(offset_store) => request.add("offset", offset_store.load()?),
+            None => request.add("offset", self.offset)
         }
-        parameters.pop();
-        let res = self.send_request("addStickerToSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
+        request.add("limit", limit);
+        request.add("timeout", timeout);
+        request.add("allowed_updates", allowed_updates);
+        let res = self.send_with_retries(request)?;
+        let (ret, last) = decode_updates(res, self.offset_store.as_ref(), self.on_decode_error.as_ref())?;
+        if let (None, Some(last)) = (&self.offset_store, last) {
+            // the next request with this offset confirms the updates
+            self.offset = last + 1;
         }
+        Ok(ret)
     }
 
-    pub fn add_sticker_position_in_set(&mut self, sticker: String, position: i32) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, sticker, position
-        }
-        parameters.pop();
-        let res = self.send_request("setStickerPositionInSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
+    /// download_url returns the URL to download a file from, the file_path is taken from get_file.
+    /// The URL contains the bot token, so it should not be shared.
+    pub fn download_url(&self, file_path: &str) -> String {
+        format!("{}/file/bot{}/{}", self.api_url, self.key, file_path)
     }
 
-    pub fn delete_sticker_from_chat(&mut self, sticker: String) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, sticker
-        }
-        parameters.pop();
-        let res = self.send_request("deleteStickerFromSet".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
+    /// download_file streams a file received by get_file into `writer` and returns the number of bytes written.
+    /// `progress` is called after every chunk with the bytes downloaded so far and the total size, if known.
+    /// ```ignore
+    /// let file = bot.get_file(document.file_id)?;
+    /// let mut buffer = vec![];
+    /// bot.download_file(&file, &mut buffer, Some(&mut |done, total| println!("{}/{:?}", done, total)))?;
+    /// ```
+    pub fn download_file<W: Write>(&self, file: &File, writer: &mut W,
+                                   progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, TelegramError> {
+        let file_path = download_path(file)?;
+        let mut request = self.client.get(self.download_url(file_path));
+        if let Some(timeout) = self.download_timeout {
+            request = request.timeout(timeout);
+        }
+        let mut res = request.send()?.error_for_status()?;
+        let total = res.content_length().or_else(|| file.file_size.map(|s| s as u64));
+        copy_file(&mut res, writer, total, progress)
+    }
+
+    /// download_file_to_path works like download_file, but writes into a file at `path`.
+    /// The file is only created once the download is complete, nothing is left behind if it fails.
+    pub fn download_file_to_path<P: AsRef<Path>>(&self, file: &File, path: P,
+                                                 progress: Option<&mut dyn FnMut(u64, Option<u64>)>) -> Result<u64, TelegramError> {
+        let path = path.as_ref();
+        let part = part_path(path);
+        let res = fs::File::create(&part)
+            .map_err(TelegramError::from)
+            .and_then(|f| self.download_file(file, &mut BufWriter::new(f), progress));
+        match res {
+            Ok(written) => {
+                fs::rename(&part, path)?;
+                Ok(written)
+            },
+            Err(e) => {
+                fs::remove_file(&part).ok();
+                Err(e)
+            }
         }
     }
 
-    pub fn set_sticker_thumb(&mut self, name: String, user_id: i64, thumb: Option<String>) -> bool {
-        let mut parameters = "".to_string();
-        expand_parameters_into_string! {
-            parameters, name, user_id
-        }
-        expand_parameters_opt_into_string! {
-            parameters, thumb
-        }
-        parameters.pop();
-        let res = self.send_request("setStickerSetThumb".to_string(), parameters);
-        expand_make_request_to_bool! {
-            res
-        }
+    /// start_webhook starts a small HTTP server on `address`, which receives the updates
+    /// Telegram posts to `secret_path` and sends them into `tx`, just like start_polling.
+    /// Updates which can't be decoded are passed to the decode error handler of the bot.
+    /// The webhook itself still has to be registered with set_webhook, usually behind a reverse proxy.
+    /// ```ignore
+    /// let (tx, rx) = mpsc::channel();
+    /// bot.set_webhook("https://example.com/my-secret-path".to_string(), None, None, None, None, None)?;
+    /// let server = bot.start_webhook("127.0.0.1:8443", "/my-secret-path", tx)?;
+    /// let received = rx.recv().unwrap();
+    /// println!("Got: {}", received);
+    /// ```
+    pub fn start_webhook<A: ToSocketAddrs>(&self, address: A, secret_path: &str, tx: Sender<Update>) -> std::io::Result<WebhookServer> {
+        WebhookServer::start_with_decode_error_handler(address, secret_path, tx, self.on_decode_error.clone())
     }
 }
 
+telegram_methods!(expand_method_structs);
+telegram_methods!(expand_blocking_methods);
+
 impl Clone for Bot {
     fn clone(&self) -> Self {
         Bot {
             key: self.key.clone(),
-            offset: self.offset
+            api_url: self.api_url.clone(),
+            offset: self.offset,
+            retry_policy: self.retry_policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            offset_store: self.offset_store.clone(),
+            on_decode_error: self.on_decode_error.clone(),
+            client: self.client.clone(),
+            download_timeout: self.download_timeout
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_response_ok() {
+        let res = decode_response(r#"{"ok":true,"result":{"id":1234,"is_bot":true,"first_name":"groot"}}"#).unwrap();
+        let user = GetMe::decode(res).unwrap();
+        assert_eq!(user.id, 1234);
+    }
+
+    #[test]
+    fn test_decode_message_or_true() {
+        let res = decode_response(r#"{"ok":true,"result":true}"#).unwrap();
+        assert!(matches!(SetGameScore::decode(res), Ok(MessageOrTrue::True(true))));
+        let res = decode_response(r#"{"ok":true,"result":{"message_id":1,"date":0,"chat":{"id":2,"type":"private"}}}"#).unwrap();
+        assert_eq!(EditMessageText::decode(res).unwrap().message().unwrap().message_id, 1);
+    }
+
+    #[test]
+    fn test_decode_invalid_result() {
+        let res = decode_response(r#"{"ok":true,"result":{"id":1234,"first_name":"groot"}}"#).unwrap();
+        assert!(matches!(GetMe::decode(res), Err(TelegramError::Decode(_))));
+    }
+
+    #[test]
+    fn test_decode_updates() {
+        let res = serde_json::json!([{"update_id":5},{"update_id":6,"message":{"message_id":"invalid"}},{"update_id":7}]);
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        let e = errors.clone();
+        let handler: DecodeErrorHandler = Arc::new(move |update, _| e.lock().unwrap().push(update["update_id"].clone()));
+        let offset = AckedOffset::new(crate::offset::MemoryOffsetStore::new());
+        let (updates, last) = decode_updates(res, Some(&offset), Some(&handler)).unwrap();
+        assert_eq!(updates.iter().map(|u| u.update_id).collect::<Vec<i64>>(), vec![5, 7]);
+        assert_eq!(last, Some(7));
+        assert_eq!(*errors.lock().unwrap(), vec![6]);
+        offset.ack(updates[0].update_id).unwrap();
+        assert_eq!(offset.load().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_decode_response_api_error() {
+        let res = decode_response(r#"{"ok":false,"error_code":429,"description":"Too Many Requests: retry after 5","parameters":{"retry_after":5}}"#);
+        match res {
+            Err(TelegramError::Api { error_code, description, parameters }) => {
+                assert_eq!(error_code, 429);
+                assert_eq!(description, "Too Many Requests: retry after 5");
+                assert_eq!(parameters.unwrap().retry_after, Some(5));
+            },
+            _ => panic!("expected an api error")
+        }
+    }
+
+    #[test]
+    fn test_copy_file() {
+        let data = vec![7u8; 20000];
+        let mut out = vec![];
+        let mut calls = vec![];
+        let written = copy_file(&mut data.as_slice(), &mut out, Some(20000), Some(&mut |done, total| calls.push((done, total)))).unwrap();
+        assert_eq!(written, 20000);
+        assert_eq!(out, data);
+        assert_eq!(calls.last(), Some(&(20000, Some(20000))));
+    }
+
+    #[test]
+    fn test_copy_file_too_large() {
+        let mut data = std::io::repeat(0).take(MAX_DOWNLOAD_SIZE + 1);
+        let res = copy_file(&mut data, &mut std::io::sink(), None, None);
+        assert!(matches!(res, Err(TelegramError::FileTooLarge(_))));
+    }
+
+    #[test]
+    fn test_download_file_to_path_failed() {
+        let bot = Bot::new("KEY".to_string()).with_api_url("http://127.0.0.1:1");
+        let mut file = File::empty();
+        file.file_path = Some("documents/file_1.txt".to_string());
+        let path = std::env::temp_dir().join(format!("telegram-api-rs-download-{}", std::process::id()));
+        assert!(matches!(bot.download_file_to_path(&file, &path, None), Err(TelegramError::Transport(_))));
+        assert!(!path.exists());
+        assert!(!part_path(&path).exists());
+    }
+
+    #[test]
+    fn test_wrapper_parameters() {
+        let mut bot = Bot::new("KEY".to_string()).with_api_url("http://127.0.0.1:1");
+        let res = bot.send_message("@channel", "hi", None, None, None, None, None, None, None);
+        assert!(matches!(res, Err(TelegramError::Transport(_))));
+        assert!(matches!(bot.send_chat_action(-1001234, ChatAction::Typing), Err(TelegramError::Transport(_))));
+    }
+
+    #[test]
+    fn test_decode_response_invalid_json() {
+        assert!(matches!(decode_response("<html>Bad Gateway</html>"), Err(TelegramError::Decode(_))));
+        assert!(matches!(decode_response(r#"{"result":true}"#), Err(TelegramError::Decode(_))));
+    }
+
+    #[test]
+    fn test_request_struct() {
+        let request = SendMessage::new(1234, "hello").disable_notification(true).into_request();
+        assert_eq!(request.url("https://api.telegram.org", "KEY"), "https://api.telegram.org/botKEY/sendMessage");
+        assert_eq!(request.body(), r#"{"chat_id":1234,"text":"hello","disable_notification":true}"#);
+    }
+
+    #[test]
+    fn test_request_struct_parse_mode() {
+        let request = SendMessage::new(1234, "*hello*").parse_mode(ParseMode::MarkdownV2).into_request();
+        assert_eq!(request.body(), r#"{"chat_id":1234,"text":"*hello*","parse_mode":"MarkdownV2"}"#);
+    }
+
+    #[test]
+    fn test_request_struct_chat_id() {
+        let request = ForwardMessage::new("@target", -1001234, 10).into_request();
+        assert_eq!(request.body(), r#"{"chat_id":"@target","from_chat_id":-1001234,"message_id":10}"#);
+        assert_eq!(request.chat_id(), Some(ChatId::Username("@target".to_string())));
+        assert_eq!(SendMessage::new(1234, "hi").into_request().chat_id(), Some(ChatId::Id(1234)));
+        assert_eq!(GetMe::new().into_request().chat_id(), None);
+    }
+
+    #[test]
+    fn test_request_struct_enums() {
+        let request = SendDice::new(1234).emoji(DiceEmoji::Basketball).into_request();
+        assert_eq!(request.body(), r#"{"chat_id":1234,"emoji":"🏀"}"#);
+        let request = SendChatAction::new(1234, ChatAction::Typing).into_request();
+        assert_eq!(request.body(), r#"{"chat_id":1234,"action":"typing"}"#);
+    }
+
+    #[test]
+    fn test_request_struct_required_parameters() {
+        let request = CreateNewStickerSet::new(1234, "cats_by_bot", "Cats", "😺")
+            .png_sticker("AgACAgIAAxkBAAI");
+        assert_eq!(request.emojis, "😺");
+        assert!(request.contains_masks.is_none());
+        assert!(matches!(request.png_sticker, Some(InputFile::FileId(_))));
+    }
+
+    #[test]
+    fn test_request_struct_reply_markup() {
+        let mut force_reply = ForceReply::empty();
+        force_reply.force_reply = true;
+        let request = SendMessage::new(1234, "hello").reply_markup(force_reply).into_request();
+        assert_eq!(request.body(), r#"{"chat_id":1234,"text":"hello","reply_markup":{"force_reply":true}}"#);
+    }
+
+    #[test]
+    fn test_stop_polling() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let mut bot = Bot::new("KEY".to_string()).with_api_url("http://127.0.0.1:1");
+        let polling = bot.start_polling(tx);
+        thread::sleep(Duration::from_millis(100));
+        assert!(polling.is_running());
+        let start = std::time::Instant::now();
+        polling.stop();
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_polling_stops_on_fatal_error() {
+        let (url, api) = crate::testing::fake_api(vec![
+            r#"{"ok":false,"error_code":502,"description":"Bad Gateway"}"#,
+            r#"{"ok":false,"error_code":409,"description":"Conflict: terminated by other getUpdates request"}"#,
+        ]);
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        let e = errors.clone();
+        let config = PollingConfig {
+            error_backoff: Duration::from_millis(10),
+            on_error: Some(Arc::new(move |error| e.lock().unwrap().push(error.to_string()))),
+            ..PollingConfig::default()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut bot = Bot::new("KEY".to_string()).with_api_url(&url);
+        let polling = bot.start_polling_with_config(config, tx);
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_err());
+        assert!(!polling.is_running());
+        assert!(matches!(polling.stop(), Some(TelegramError::Api { error_code: 409, .. })));
+        assert_eq!(errors.lock().unwrap().len(), 2);
+        assert_eq!(api.join().unwrap().len(), 2);
+    }
+}
@@ -12,441 +12,360 @@
 //! let user = User::empty()
 //! ```
 //! All types can also be displayed and cloned.
+//! They derive serde's `Serialize` and `Deserialize`, so they can be used with `serde_json` directly
+//! ```ignore
+//! let user: User = serde_json::from_str(text)?;
+//! ```
 
 extern crate json;
-extern crate rustc_serialize;
 
 use json::JsonValue;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
 use std::fmt;
+use std::path::PathBuf;
 use crate::*;
 
-#[derive(Debug, Clone, Copy)]
+add_functionality_string_enum! {
 pub enum MessageEntityType {
-    Mention,
-    Hashtag,
-    Cashtag,
-    BotCommand,
-    Url,
-    Email,
-    PhoneNumber,
-    Bold,
-    Italic,
-    Underline,
-    Strikethrough,
-    Code,
-    Pre,
-    TextLink,
-    TextMention
-}
-
-impl MessageEntityType {
-    fn from_string(s: String) -> MessageEntityType {
-        let s = s.as_str();
-        match s {
-            "mention" => MessageEntityType::Mention,
-            "hashtag" => MessageEntityType::Hashtag,
-            "cashtag" => MessageEntityType::Cashtag,
-            "bot_command" => MessageEntityType::BotCommand,
-            "url" => MessageEntityType::Url,
-            "email" => MessageEntityType::Email,
-            "phone_number" => MessageEntityType::PhoneNumber,
-            "bold" => MessageEntityType::Bold,
-            "italic" => MessageEntityType::Italic,
-            "underline" => MessageEntityType::Underline,
-            "strikethrough" => MessageEntityType::Strikethrough,
-            "code" => MessageEntityType::Code,
-            "pre" => MessageEntityType::Pre,
-            "text_link" => MessageEntityType::TextLink,
-            "text_mention" => MessageEntityType::TextMention,
-            _ => panic!("can't find this MessageEntityType")
-        }
-    }
-}
-
-impl fmt::Display for MessageEntityType {
+    Mention = "mention",
+    Hashtag = "hashtag",
+    Cashtag = "cashtag",
+    BotCommand = "bot_command",
+    Url = "url",
+    Email = "email",
+    PhoneNumber = "phone_number",
+    Bold = "bold",
+    Italic = "italic",
+    Underline = "underline",
+    Strikethrough = "strikethrough",
+    Code = "code",
+    Pre = "pre",
+    TextLink = "text_link",
+    TextMention = "text_mention"
+}
+
+/// ParseMode tells Telegram how to find the entities in a text, see the `formatting` module
+/// for building correctly escaped texts
+pub enum ParseMode {
+    MarkdownV2 = "MarkdownV2",
+    Html = "HTML",
+    /// legacy Markdown, only kept for backward compatibility by Telegram
+    Markdown = "Markdown"
+}
+
+pub enum ChatType {
+    Private = "private",
+    Group = "group",
+    Supergroup = "supergroup",
+    Channel = "channel",
+    /// only used by `InlineQuery`, for queries sent from the private chat with the bot
+    Sender = "sender"
+}
+
+pub enum ChatMemberStatus {
+    Member = "member",
+    Creator = "creator",
+    Administrator = "administrator",
+    Restricted = "restricted",
+    Left = "left",
+    Kicked = "kicked"
+}
+
+/// ChatAction tells the user what the bot is doing, see `send_chat_action`
+pub enum ChatAction {
+    Typing = "typing",
+    UploadPhoto = "upload_photo",
+    RecordVideo = "record_video",
+    UploadVideo = "upload_video",
+    RecordVoice = "record_voice",
+    UploadVoice = "upload_voice",
+    UploadDocument = "upload_document",
+    FindLocation = "find_location",
+    RecordVideoNote = "record_video_note",
+    UploadVideoNote = "upload_video_note"
+}
+
+pub enum PollType {
+    Regular = "regular",
+    Quiz = "quiz"
+}
+
+/// MaskPoint is the part of the face a mask is placed relative to
+pub enum MaskPoint {
+    Forehead = "forehead",
+    Eyes = "eyes",
+    Mouth = "mouth",
+    Chin = "chin"
+}
+
+pub enum DiceEmoji {
+    Dice = "🎲",
+    Darts = "🎯",
+    Basketball = "🏀",
+    Football = "⚽",
+    Bowling = "🎳",
+    SlotMachine = "🎰"
+}
+
+pub enum InputMediaType {
+    Photo = "photo",
+    Video = "video",
+    Animation = "animation",
+    Audio = "audio",
+    Document = "document"
+}}
+
+/// InputFile is used for all parameters taking a file.
+/// Existing files can be referenced by file_id or URL, new files are uploaded from a path or from memory.
+/// ```ignore
+/// bot.send_photo(chat_id, InputFile::Path(PathBuf::from("cat.jpg")), ...);
+/// bot.send_photo(chat_id, InputFile::Memory { file_name: "cat.jpg".to_string(), data: bytes }, ...);
+/// ```
+#[derive(Debug, Clone)]
+pub enum InputFile {
+    FileId(String),
+    Url(String),
+    Path(PathBuf),
+    Memory { file_name: String, data: Vec<u8> }
+}
+
+impl InputFile {
+    /// is_upload returns true if the file has to be sent as multipart/form-data
+    pub fn is_upload(&self) -> bool {
+        matches!(self, InputFile::Path(_) | InputFile::Memory { .. })
+    }
+
+    pub(crate) fn file_name(&self) -> String {
+        match self {
+            InputFile::Path(p) => p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            InputFile::Memory { file_name, .. } => file_name.clone(),
+            InputFile::FileId(s) | InputFile::Url(s) => s.clone()
+        }
+    }
+}
+
+impl From<String> for InputFile {
+    fn from(s: String) -> Self {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            InputFile::Url(s)
+        } else {
+            InputFile::FileId(s)
+        }
+    }
+}
+
+impl From<&str> for InputFile {
+    fn from(s: &str) -> Self {
+        InputFile::from(s.to_string())
+    }
+}
+
+impl From<PathBuf> for InputFile {
+    fn from(p: PathBuf) -> Self {
+        InputFile::Path(p)
+    }
+}
+
+impl fmt::Display for InputFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputFile::FileId(s) | InputFile::Url(s) => write!(f, "{}", s),
+            _ => write!(f, "attach://{}", self.file_name())
+        }
+    }
+}
+
+impl Default for InputFile {
+    fn default() -> Self {
+        InputFile::FileId(String::new())
+    }
+}
+
+/// files to upload are serialized as their `attach://` reference, see `Display`
+impl Serialize for InputFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for InputFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(InputFile::from)
+    }
+}
+
+/// ChatId is used for all parameters taking a chat.
+/// Channels and supergroups can also be referenced by their username.
+/// ```ignore
+/// bot.send(SendMessage::new(1234, "hello"))?;
+/// bot.send(SendMessage::new("@channelusername", "hello"))?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatId {
+    Id(i64),
+    Username(String)
+}
+
+impl Default for ChatId {
+    fn default() -> Self {
+        ChatId::Id(0)
+    }
+}
+
+impl From<i64> for ChatId {
+    fn from(id: i64) -> Self {
+        ChatId::Id(id)
+    }
+}
+
+impl From<String> for ChatId {
+    fn from(username: String) -> Self {
+        ChatId::Username(username)
+    }
+}
+
+impl From<&str> for ChatId {
+    fn from(username: &str) -> Self {
+        ChatId::Username(username.to_string())
+    }
+}
+
+impl fmt::Display for ChatId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            MessageEntityType::Mention => write!(f, "mention"),
-            MessageEntityType::Hashtag => write!(f, "hashtag"),
-            MessageEntityType::Cashtag => write!(f, "cashtag"),
-            MessageEntityType::BotCommand => write!(f, "bot_command"),
-            MessageEntityType::Url => write!(f, "url"),
-            MessageEntityType::Email => write!(f, "email"),
-            MessageEntityType::PhoneNumber => write!(f, "phone_number"),
-            MessageEntityType::Bold => write!(f, "bold"),
-            MessageEntityType::Italic => write!(f, "italic"),
-            MessageEntityType::Underline => write!(f, "underline"),
-            MessageEntityType::Strikethrough => write!(f, "strikethrough"),
-            MessageEntityType::Code => write!(f, "code"),
-            MessageEntityType::Pre => write!(f, "pre"),
-            MessageEntityType::TextLink => write!(f, "text_link"),
-            MessageEntityType::TextMention => write!(f, "text_mention"),
-        }
-    }
-}
-
-vec_to_json_array! {
-    vec_me_to_json_array(MessageEntity)
-    vec_i32_to_json_array(i32)
-    vec_string_to_json_array(String)
-    vec_po_to_json_array(PollOption)
-    vec_update_to_json_array(Update)
-    vec_user_to_json_array(User)
-    vec_photo_size_to_json_array(PhotoSize)
-    vec_sticker_to_json_array(Sticker)
-    vec_keyboard_button_to_json_array(KeyboardButton)
-    vec_inline_keyboard_button_to_json_array(InlineKeyboardButton)
-    vec_input_media_to_json_array(InputMedia)
-    vec_message_to_json_array(Message)
-    vec_chat_member_to_json_array(ChatMember)
-    vec_bot_command_to_json_array(BotCommand)
-}
-
-vec_vec_to_json_array! {
-    vec_vec_photo_size_to_json_array(PhotoSize, vec_photo_size_to_json_array)
-    vec_vec_keyboard_button_to_json_array(KeyboardButton, vec_keyboard_button_to_json_array)
-    vec_vec_inline_keyboard_button_to_json_array(InlineKeyboardButton, vec_inline_keyboard_button_to_json_array)
-}
-
-pub(crate) trait Custom {
-    fn from_json(s: JsonValue) -> Self;
-    fn create_json(j: JsonValue, v: Self, name: &'static str) -> JsonValue;
-    fn to_json(v: Self) -> JsonValue;
-    fn push(j: Vec<String>, v: Self, name: &'static str) -> Vec<String>;
-    fn default() -> Self;
-    fn url_encode(v: Self) -> String;
-}
-
-expand_custom_direct_i! {
-    impl Custom for i64 (as_i64, unwrap, 0)
-    impl Custom for i32 (as_i32, unwrap, 0)
-    impl Custom for f64 (as_f64, unwrap, 0.0)
-}
-
-expand_custom_vec! {
-    impl Custom for Vec<i32> (as_vec_i32, unwrap, [].to_vec(), vec_i32_to_json_array)
-    impl Custom for Vec<Update> (as_vec_update, unwrap, [].to_vec(), vec_update_to_json_array)
-    impl Custom for Vec<PollOption> (as_vec_poll_option, unwrap, [].to_vec(), vec_po_to_json_array)
-    impl Custom for Vec<User> (as_vec_user, unwrap, [].to_vec(), vec_user_to_json_array)
-    impl Custom for Vec<Sticker> (as_vec_sticker, unwrap, [].to_vec(), vec_sticker_to_json_array)
-    impl Custom for Vec<String> (as_vec_string, unwrap, [].to_vec(), vec_string_to_json_array)
-    impl Custom for Vec<MessageEntity> (as_vec_message_entity, unwrap, [].to_vec(), vec_me_to_json_array)
-    impl Custom for Vec<InputMedia> (as_vec_input_media, unwrap, [].to_vec(), vec_input_media_to_json_array)
-    impl Custom for Vec<Message> (as_vec_message, unwrap, [].to_vec(), vec_message_to_json_array)
-    impl Custom for Vec<ChatMember> (as_vec_chat_member, unwrap, [].to_vec(), vec_chat_member_to_json_array)
-    impl Custom for Vec<BotCommand> (as_vec_bot_command, unwrap, [].to_vec(), vec_bot_command_to_json_array)
-}
-
-expand_custom_vec_vec! {
-    impl Custom for Vec<Vec<PhotoSize>> (as_vec_vec_photo_size, unwrap, [].to_vec(), vec_vec_photo_size_to_json_array)
-    impl Custom for Vec<Vec<KeyboardButton>> (as_vec_vec_keyboard_button, unwrap, [].to_vec(), vec_vec_keyboard_button_to_json_array)
-    impl Custom for Vec<Vec<InlineKeyboardButton>> (as_vec_vec_inline_keyboard_button, unwrap, [].to_vec(), vec_vec_inline_keyboard_button_to_json_array)
-}
-
-expand_custom_direct_bool! {
-    impl Custom for bool (as_bool, unwrap, false)
-}
-
-expand_custom! {
-    impl Custom for String (to_string, clone, "".to_string())
-    impl Custom for MessageEntityType (as_message_entity_type, unwrap, MessageEntityType::Mention)
-}
-
-expand_custom_direct_object! {
-    impl Custom for Update (as_update, unwrap, Update::empty())
-    impl Custom for User (as_user, unwrap, User::empty())
-    impl Custom for Message (as_message, unwrap, Message::empty())
-    impl Custom for ChatInviteLink (as_chat_invite_link, unwrap, ChatInviteLink::empty())
-    impl Custom for ChatMember (as_chat_member, unwrap, ChatMember::empty())
-    impl Custom for Chat (as_chat, unwrap, Chat::empty())
-    impl Custom for Sticker (as_sticker, unwrap, Sticker::empty())
-    impl Custom for KeyboardButton (as_keyboard_button, unwrap, KeyboardButton::empty())
-    impl Custom for ReplyKeyboardMarkup (as_reply_keyboard_markup, unwrap, ReplyKeyboardMarkup::empty())
-    impl Custom for ReplyKeyboardRemove (as_reply_keyboard_remove, unwrap, ReplyKeyboardRemove::empty())
-    impl Custom for ForceReply (as_force_reply, unwrap, ForceReply::empty())
-    impl Custom for Location (as_location, unwrap, Location::empty())
-    impl Custom for PollOption (as_poll_option, unwrap, PollOption::empty())
-    impl Custom for MessageEntity (as_message_entity, unwrap, MessageEntity::empty())
-    impl Custom for PhotoSize (as_photo_size, unwrap, PhotoSize::empty())
-    impl Custom for InlineKeyboardMarkup (as_inline_keyboard_markup, unwrap, InlineKeyboardMarkup::empty())
-    impl Custom for InlineKeyboardButton (as_inline_keyboard_button, unwrap, InlineKeyboardButton::empty())
-    impl Custom for InputMedia (as_input_media, unwrap, InputMedia::empty())
-    impl Custom for UserProfilePhotos (as_user_profile_photos, unwrap, UserProfilePhotos::empty())
-    impl Custom for ChatPermissions (as_chat_permissions, unwrap, ChatPermissions::empty())
-    impl Custom for BotCommand (as_bot_command, unwrap, BotCommand::empty())
-    impl Custom for Poll (as_poll, unwrap, Poll::empty())
-    impl Custom for StickerSet (as_sticker_set, unwrap, StickerSet::empty())
-    impl Custom for MaskPosition (as_mask_position, unwrap, MaskPosition::empty())
-}
-
-expand_custom_option! {
-    impl Custom for Option<bool> (as_bool, unwrap, None)
-    impl Custom for Option<i32> (as_i32, unwrap, None)
-    impl Custom for Option<i64> (as_i64, unwrap, None)
-    impl Custom for Option<f64> (as_f64, unwrap, None)
-    impl Custom for Option<String> (to_string, clone, None)
-    impl Custom for Option<User> (as_user, unwrap, None)
-    impl Custom for Option<Message> (as_message, unwrap, None)
-    impl Custom for Option<InlineKeyboardMarkup> (as_inline_keyboard_markup, unwrap, None)
-    impl Custom for Option<VoiceChatStarted> (as_voice_chat_started, unwrap, None)
-    impl Custom for Option<VoiceChatEnded> (as_voice_chat_ended, unwrap, None)
-    impl Custom for Option<VoiceChatScheduled> (as_voice_chat_scheduled, unwrap, None)
-    impl Custom for Option<VoiceChatParticipantsInvited> (as_voice_chat_participants_invited, unwrap, None)
-    impl Custom for Option<ProximityAlertTriggered> (as_proximity_alert_triggered, unwrap, None)
-    impl Custom for Option<MessageAutoDeleteTimerChanged> (as_message_auto_delete_timer_changed, unwrap, None)
-    impl Custom for Option<PhotoSize> (as_photo_size, unwrap, None)
-    impl Custom for Option<Contact> (as_contact, unwrap, None)
-    impl Custom for Option<Dice> (as_dice, unwrap, None)
-    impl Custom for Option<Poll> (as_poll, unwrap, None)
-    impl Custom for Option<Venue> (as_venue, unwrap, None)
-    impl Custom for Option<ChatPermissions> (as_chat_permissions, unwrap, None)
-    impl Custom for Option<Location> (as_location, unwrap, None)
-    impl Custom for Option<Chat> (as_chat, unwrap, None)
-    impl Custom for Option<ChatPhoto> (as_chat_photo, unwrap, None)
-    impl Custom for Option<Animation> (as_animation, unwrap, None)
-    impl Custom for Option<Audio> (as_audio, unwrap, None)
-    impl Custom for Option<ChatInviteLink> (as_chat_invite_link, unwrap, None)
-    impl Custom for Option<ChatMember> (as_chat_member, unwrap, None)
-    impl Custom for Option<Document> (as_document, unwrap, None)
-    impl Custom for Option<LoginUrl> (as_login_url, unwrap, None)
-    impl Custom for Option<Sticker> (as_sticker, unwrap, None)
-    impl Custom for Option<Video> (as_video, unwrap, None)
-    impl Custom for Option<VideoNote> (as_video_note, unwrap, None)
-    impl Custom for Option<Voice> (as_voice, unwrap, None)
-    impl Custom for Option<MaskPosition> (as_mask_position, unwrap, None)
-    impl Custom for Option<KeyboardButtonPollType> (as_keyboard_button_poll_type, unwrap, None)
-    impl Custom for Option<ChatLocation> (as_chat_location, unwrap, None)
-    impl Custom for Option<CallbackQuery> (as_callback_query, unwrap, None)
-    impl Custom for Option<PollAnswer> (as_poll_answer, unwrap, None)
-    impl Custom for Option<ChatMemberUpdated> (as_chat_member_updated, unwrap, None)
-}
-
-expand_custom_box! {
-    impl Custom for Box<Chat> (as_box_chat, unwrap, Box::new(Chat::empty()))
-}
-
-expand_custom_option_box! {
-    impl Custom for Option<Box<Chat>> (as_box_chat, unwrap, None)
-    impl Custom for Option<Box<Message>> (as_box_message, unwrap, None)
-}
-
-expand_custom_option_vec! {
-    impl Custom for Option<Vec<MessageEntity>> (as_vec_message_entity, unwrap, None, vec_me_to_json_array)
-    impl Custom for Option<Vec<PhotoSize>> (as_vec_photo_size, unwrap, None, vec_photo_size_to_json_array)
-    impl Custom for Option<Vec<User>> (as_vec_user, unwrap, None, vec_user_to_json_array)
-}
-
-trait JsonExt {
-    fn as_update(&self) -> Option<Update>;
-    fn as_user(&self) -> Option<User>;
-    fn as_chat(&self) -> Option<Chat>;
-    fn as_message(&self) -> Option<Message>;
-    fn as_sticker(&self) -> Option<Sticker>;
-    fn as_keyboard_button_poll_type(&self) -> Option<KeyboardButtonPollType>;
-    fn as_keyboard_button(&self) -> Option<KeyboardButton>;
-    fn as_reply_keyboard_markup(&self) -> Option<ReplyKeyboardMarkup>;
-    fn as_reply_keyboard_remove(&self) -> Option<ReplyKeyboardRemove>;
-    fn as_force_reply(&self) -> Option<ForceReply>;
-    fn as_location(&self) -> Option<Location>;
-    fn as_poll_option(&self) -> Option<PollOption>;
-    fn as_message_entity(&self) -> Option<MessageEntity>;
-    fn as_photo_size(&self) -> Option<PhotoSize>;
-    fn as_mask_position(&self) -> Option<MaskPosition>;
-    fn as_inline_keyboard_markup(&self) -> Option<InlineKeyboardMarkup>;
-    fn as_inline_keyboard_button(&self) -> Option<InlineKeyboardButton>;
-    fn as_voice_chat_started(&self) -> Option<VoiceChatStarted>;
-    fn as_voice_chat_ended(&self) -> Option<VoiceChatEnded>;
-    fn as_voice_chat_scheduled(&self) -> Option<VoiceChatScheduled>;
-    fn as_voice_chat_participants_invited(&self) -> Option<VoiceChatParticipantsInvited>;
-    fn as_proximity_alert_triggered(&self) -> Option<ProximityAlertTriggered>;
-    fn as_message_auto_delete_timer_changed(&self) -> Option<MessageAutoDeleteTimerChanged>;
-    fn as_contact(&self) -> Option<Contact>;
-    fn as_dice(&self) -> Option<Dice>;
-    fn as_poll(&self) -> Option<Poll>;
-    fn as_venue(&self) -> Option<Venue>;
-    fn as_chat_permissions(&self) -> Option<ChatPermissions>;
-    fn as_chat_photo(&self) -> Option<ChatPhoto>;
-    fn as_chat_member(&self) -> Option<ChatMember>;
-    fn as_chat_location(&self) -> Option<ChatLocation>;
-    fn as_animation(&self) -> Option<Animation>;
-    fn as_audio(&self) -> Option<Audio>;
-    fn as_chat_invite_link(&self) -> Option<ChatInviteLink>;
-    fn as_document(&self) -> Option<Document>;
-    fn as_video(&self) -> Option<Video>;
-    fn as_video_note(&self) -> Option<VideoNote>;
-    fn as_voice(&self) -> Option<Voice>;
-    fn as_login_url(&self) -> Option<LoginUrl>;
-    fn as_callback_query(&self) -> Option<CallbackQuery>;
-    fn as_poll_answer(&self) -> Option<PollAnswer>;
-    fn as_chat_member_updated(&self) -> Option<ChatMemberUpdated>;
-    fn as_input_media(&self) -> Option<InputMedia>;
-    fn as_user_profile_photos(&self) -> Option<UserProfilePhotos>;
-    fn as_bot_command(&self) -> Option<BotCommand>;
-    fn as_sticker_set(&self) -> Option<StickerSet>;
-    fn as_vec_poll_option(&self) -> Option<Vec<PollOption>>;
-    fn as_vec_string(&self) -> Option<Vec<String>>;
-    fn as_vec_update(&self) -> Option<Vec<Update>>;
-    fn as_vec_user(&self) -> Option<Vec<User>>;
-    fn as_vec_photo_size(&self) -> Option<Vec<PhotoSize>>;
-    fn as_vec_sticker(&self) -> Option<Vec<Sticker>>;
-    fn as_vec_keyboard_button(&self) -> Option<Vec<KeyboardButton>>;
-    fn as_vec_inline_keyboard_button(&self) -> Option<Vec<InlineKeyboardButton>>;
-    fn as_vec_input_media(&self) -> Option<Vec<InputMedia>>;
-    fn as_vec_message(&self) -> Option<Vec<Message>>;
-    fn as_vec_chat_member(&self) -> Option<Vec<ChatMember>>;
-    fn as_vec_bot_command(&self) -> Option<Vec<BotCommand>>;
-    fn as_vec_vec_photo_size(&self) -> Option<Vec<Vec<PhotoSize>>>;
-    fn as_vec_vec_keyboard_button(&self) -> Option<Vec<Vec<KeyboardButton>>>;
-    fn as_vec_vec_inline_keyboard_button(&self) -> Option<Vec<Vec<InlineKeyboardButton>>>;
-    fn as_vec_i32(&self) -> Option<Vec<i32>>;
-    fn as_message_entity_type(&self) -> Option<MessageEntityType>;
-    fn as_vec_message_entity(&self) -> Option<Vec<MessageEntity>>;
-    fn as_box_chat(&self) -> Option<Box<Chat>>;
-    fn as_box_message(&self) -> Option<Box<Message>>;
-}
-
-impl JsonExt for JsonValue {
-    as_custom! {
-        fn as_update(&self) -> Option<Update>
-        fn as_user(&self) -> Option<User>
-        fn as_chat(&self) -> Option<Chat>
-        fn as_message(&self) -> Option<Message>
-        fn as_sticker(&self) -> Option<Sticker>
-        fn as_keyboard_button_poll_type(&self) -> Option<KeyboardButtonPollType>
-        fn as_keyboard_button(&self) -> Option<KeyboardButton>
-        fn as_reply_keyboard_markup(&self) -> Option<ReplyKeyboardMarkup>
-        fn as_reply_keyboard_remove(&self) -> Option<ReplyKeyboardRemove>
-        fn as_force_reply(&self) -> Option<ForceReply>
-        fn as_inline_keyboard_button(&self) -> Option<InlineKeyboardButton>
-        fn as_location(&self) -> Option<Location>
-        fn as_poll_option(&self) -> Option<PollOption>
-        fn as_photo_size(&self) -> Option<PhotoSize>
-        fn as_mask_position(&self) -> Option<MaskPosition>
-        fn as_message_entity(&self) -> Option<MessageEntity>
-        fn as_contact(&self) -> Option<Contact>
-        fn as_dice(&self) -> Option<Dice>
-        fn as_poll(&self) -> Option<Poll>
-        fn as_venue(&self) -> Option<Venue>
-        fn as_chat_permissions(&self) -> Option<ChatPermissions>
-        fn as_chat_photo(&self) -> Option<ChatPhoto>
-        fn as_chat_member(&self) -> Option<ChatMember>
-        fn as_chat_location(&self) -> Option<ChatLocation>
-        fn as_animation(&self) -> Option<Animation>
-        fn as_audio(&self) -> Option<Audio>
-        fn as_inline_keyboard_markup(&self) -> Option<InlineKeyboardMarkup>
-        fn as_voice_chat_started(&self) -> Option<VoiceChatStarted>
-        fn as_voice_chat_ended(&self) -> Option<VoiceChatEnded>
-        fn as_voice_chat_scheduled(&self) -> Option<VoiceChatScheduled>
-        fn as_voice_chat_participants_invited(&self) -> Option<VoiceChatParticipantsInvited>
-        fn as_proximity_alert_triggered(&self) -> Option<ProximityAlertTriggered>
-        fn as_message_auto_delete_timer_changed(&self) -> Option<MessageAutoDeleteTimerChanged>
-        fn as_chat_invite_link(&self) -> Option<ChatInviteLink>
-        fn as_document(&self) -> Option<Document>
-        fn as_video(&self) -> Option<Video>
-        fn as_video_note(&self) -> Option<VideoNote>
-        fn as_voice(&self) -> Option<Voice>
-        fn as_login_url(&self) -> Option<LoginUrl>
-        fn as_callback_query(&self) -> Option<CallbackQuery>
-        fn as_poll_answer(&self) -> Option<PollAnswer>
-        fn as_chat_member_updated(&self) -> Option<ChatMemberUpdated>
-        fn as_input_media(&self) -> Option<InputMedia>
-        fn as_user_profile_photos(&self) -> Option<UserProfilePhotos>
-        fn as_bot_command(&self) -> Option<BotCommand>
-        fn as_sticker_set(&self) -> Option<StickerSet>
-    }
-    as_vec_custom! {
-        fn as_vec_poll_option(&self) -> Option<Vec<PollOption>>
-        fn as_vec_update(&self) -> Option<Vec<Update>>
-        fn as_vec_user(&self) -> Option<Vec<User>>
-        fn as_vec_i32(&self) -> Option<Vec<i32>>
-        fn as_vec_string(&self) -> Option<Vec<String>>
-        fn as_vec_photo_size(&self) -> Option<Vec<PhotoSize>>
-        fn as_vec_sticker(&self) -> Option<Vec<Sticker>>
-        fn as_vec_keyboard_button(&self) -> Option<Vec<KeyboardButton>>
-        fn as_vec_inline_keyboard_button(&self) -> Option<Vec<InlineKeyboardButton>>
-        fn as_vec_message_entity(&self) -> Option<Vec<MessageEntity>>
-        fn as_vec_input_media(&self) -> Option<Vec<InputMedia>>
-        fn as_vec_message(&self) -> Option<Vec<Message>>
-        fn as_vec_chat_member(&self) -> Option<Vec<ChatMember>>
-        fn as_vec_bot_command(&self) -> Option<Vec<BotCommand>>
-    }
-    as_vec_vec_custom! {
-        fn as_vec_vec_photo_size(&self, as_vec_photo_size) -> Option<Vec<Vec<PhotoSize>>>
-        fn as_vec_vec_keyboard_button(&self, as_vec_keyboard_button) -> Option<Vec<Vec<KeyboardButton>>>
-        fn as_vec_vec_inline_keyboard_button(&self, as_vec_inline_keyboard_button) -> Option<Vec<Vec<InlineKeyboardButton>>>
-    }
-    as_box_custom! {
-        fn as_box_chat(&self) -> Option<Box<Chat>>
-        fn as_box_message(&self) -> Option<Box<Message>>
-    }
-    fn as_message_entity_type(&self) -> Option<MessageEntityType> {
-        if self.is_empty() { None }
-        else { Some(MessageEntityType::from_string(format!("{}", self))) }
-    }
-}
-expand_from! {
-    impl From<Update> for JsonValue
-    impl From<User> for JsonValue
-    impl From<Location> for JsonValue
-    impl From<MessageEntity> for JsonValue
-    impl From<PhotoSize> for JsonValue
-    impl From<PollOption> for JsonValue
-    impl From<InlineKeyboardButton> for JsonValue
-    impl From<KeyboardButtonPollType> for JsonValue
-    impl From<KeyboardButton> for JsonValue
-    impl From<ForceReply> for JsonValue
-    impl From<ReplyKeyboardMarkup> for JsonValue
-    impl From<ReplyKeyboardRemove> for JsonValue
-    impl From<Audio> for JsonValue
-    impl From<Animation> for JsonValue
-    impl From<ChatPhoto> for JsonValue
-    impl From<ChatPermissions> for JsonValue
-    impl From<Venue> for JsonValue
-    impl From<Poll> for JsonValue
-    impl From<Dice> for JsonValue
-    impl From<Contact> for JsonValue
-    impl From<VoiceChatScheduled> for JsonValue
-    impl From<VoiceChatStarted> for JsonValue
-    impl From<VoiceChatEnded> for JsonValue
-    impl From<VoiceChatParticipantsInvited> for JsonValue
-    impl From<ProximityAlertTriggered> for JsonValue
-    impl From<MessageAutoDeleteTimerChanged> for JsonValue
-    impl From<InlineKeyboardMarkup> for JsonValue
-    impl From<Message> for JsonValue
-    impl From<Chat> for JsonValue
-    impl From<Video> for JsonValue
-    impl From<Voice> for JsonValue
-    impl From<VideoNote> for JsonValue
-    impl From<Document> for JsonValue
-    impl From<ChatMember> for JsonValue
-    impl From<ChatInviteLink> for JsonValue
-    impl From<LoginUrl> for JsonValue
-    impl From<ChatLocation> for JsonValue
-    impl From<CallbackQuery> for JsonValue
-    impl From<PollAnswer> for JsonValue
-    impl From<ChatMemberUpdated> for JsonValue
-    impl From<InputMedia> for JsonValue
-    impl From<Sticker> for JsonValue
-    impl From<MaskPosition> for JsonValue
-    impl From<UserProfilePhotos> for JsonValue
-    impl From<BotCommand> for JsonValue
-    impl From<StickerSet> for JsonValue
+            ChatId::Id(id) => write!(f, "{}", id),
+            ChatId::Username(username) => write!(f, "{}", username)
+        }
+    }
+}
+
+/// MessageOrTrue is returned by methods editing a message, which return the edited message,
+/// or only `true` if the message was sent via inline mode and is referenced by inline_message_id
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageOrTrue {
+    Message(Message),
+    True(bool)
+}
+
+impl MessageOrTrue {
+    /// message returns the edited message, if Telegram sent it
+    pub fn message(self) -> Option<Message> {
+        match self {
+            MessageOrTrue::Message(m) => Some(m),
+            MessageOrTrue::True(_) => None
+        }
+    }
+}
+
+/// to_json_value turns any object into a `JsonValue`, keeping the order of its fields
+pub(crate) fn to_json_value<T: Serialize>(value: &T) -> JsonValue {
+    serde_json::to_value(value).map(value_to_json).unwrap_or(JsonValue::Null)
+}
+
+/// value_to_json converts a `serde_json::Value` into a `JsonValue`, keeping the order of its fields
+pub(crate) fn value_to_json(value: serde_json::Value) -> JsonValue {
+    match value {
+        serde_json::Value::Null => JsonValue::Null,
+        serde_json::Value::Bool(b) => JsonValue::Boolean(b),
+        serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+            (Some(i), _, _) => JsonValue::from(i),
+            (_, Some(u), _) => JsonValue::from(u),
+            (_, _, Some(f)) => JsonValue::from(f),
+            _ => JsonValue::Null
+        },
+        serde_json::Value::String(s) => JsonValue::from(s),
+        serde_json::Value::Array(values) => JsonValue::Array(values.into_iter().map(value_to_json).collect()),
+        serde_json::Value::Object(fields) => {
+            let mut object = json::object::Object::with_capacity(fields.len());
+            for (name, value) in fields {
+                object.insert(&name, value_to_json(value));
+            }
+            JsonValue::Object(object)
+        }
+    }
+}
+
+/// json_to_value converts a `JsonValue` into a `serde_json::Value`, keeping the order of its fields
+pub(crate) fn json_to_value(mut data: JsonValue) -> serde_json::Value {
+    match data {
+        JsonValue::Null => serde_json::Value::Null,
+        JsonValue::Boolean(b) => serde_json::Value::Bool(b),
+        JsonValue::Number(n) => {
+            let (positive, mantissa, exponent) = n.as_parts();
+            // integers are kept exact, everything else becomes a float
+            let integer = u32::try_from(exponent).ok()
+                .and_then(|e| 10u64.checked_pow(e))
+                .and_then(|factor| mantissa.checked_mul(factor));
+            match integer {
+                Some(u) if positive => serde_json::Value::from(u),
+                Some(u) if u <= i64::MAX as u64 => serde_json::Value::from(-(u as i64)),
+                _ => serde_json::Number::from_f64(f64::from(n)).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null)
+            }
+        },
+        JsonValue::Short(_) | JsonValue::String(_) => serde_json::Value::String(data.take_string().unwrap_or_default()),
+        JsonValue::Array(values) => serde_json::Value::Array(values.into_iter().map(json_to_value).collect()),
+        JsonValue::Object(ref mut fields) => serde_json::Value::Object(
+            fields.iter_mut().map(|(name, value)| (name.to_string(), json_to_value(value.take()))).collect()
+        )
+    }
+}
+
+/// without_field removes the field `name` from `data`, if it is an object
+pub(crate) fn without_field(mut data: serde_json::Value, name: &str) -> serde_json::Value {
+    if let Some(fields) = data.as_object_mut() {
+        fields.remove(name);
+    }
+    data
+}
+
+/// fields_to_string lists all fields of an object as "name: value", strings are not quoted
+pub(crate) fn fields_to_string<T: Serialize>(value: &T) -> String {
+    let fields = match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return String::new()
+    };
+    fields.iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(s) => format!("{}: {}", name, s),
+            _ => format!("{}: {}", name, value)
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
 }
 
 add_functionality!{
 pub struct Update {
     pub update_id: i64,
-    pub message: Option<Message>,
-    pub edited_message: Option<Message>,
-    pub channel_post: Option<Message>,
-    pub edited_channel_post: Option<Message>,
+    pub message: Option<Box<Message>>,
+    pub edited_message: Option<Box<Message>>,
+    pub channel_post: Option<Box<Message>>,
+    pub edited_channel_post: Option<Box<Message>>,
+    pub inline_query: Option<InlineQuery>,
+    pub chosen_inline_result: Option<ChosenInlineResult>,
     pub callback_query: Option<CallbackQuery>,
+    pub shipping_query: Option<ShippingQuery>,
+    pub pre_checkout_query: Option<PreCheckoutQuery>,
     pub poll: Option<Poll>,
     pub poll_answer: Option<PollAnswer>,
     pub my_chat_member: Option<ChatMemberUpdated>,
     pub chat_member: Option<ChatMemberUpdated>
 }
 
+pub struct WebhookInfo {
+    pub url: String,
+    pub has_custom_certificate: bool,
+    pub pending_update_count: i32,
+    pub ip_address: Option<String>,
+    pub last_error_date: Option<i32>,
+    pub last_error_message: Option<String>,
+    pub max_connections: Option<i32>,
+    pub allowed_updates: Option<Vec<String>>
+}
+
 pub struct User {
     pub id: i64,
     pub is_bot: bool,
@@ -461,7 +380,7 @@ pub struct User {
 
 pub struct Chat {
     pub id: i64,
-    pub typ: String,
+    pub typ: ChatType,
     pub title: Option<String>,
     pub username: Option<String>,
     pub first_name: Option<String>,
@@ -511,6 +430,7 @@ pub struct Message {
     pub caption_entities: Option<Vec<MessageEntity>>,
     pub contact: Option<Contact>,
     pub dice: Option<Dice>,
+    pub game: Option<Game>,
     pub poll: Option<Poll>,
     pub venue: Option<Venue>,
     pub location: Option<Location>,
@@ -526,7 +446,10 @@ pub struct Message {
     pub migrate_to_chat_id: Option<i64>,
     pub migrate_from_chat_id: Option<i64>,
     pub pinned_message: Option<Box<Message>>,
+    pub invoice: Option<Invoice>,
+    pub successful_payment: Option<SuccessfulPayment>,
     pub connected_website: Option<String>,
+    pub passport_data: Option<PassportData>,
     pub proximity_alert_triggered: Option<ProximityAlertTriggered>,
     pub voice_chat_scheduled: Option<VoiceChatScheduled>,
     pub voice_chat_started: Option<VoiceChatStarted>,
@@ -627,7 +550,7 @@ pub struct Contact {
 }
 
 pub struct Dice {
-    pub emoji: String,
+    pub emoji: DiceEmoji,
     pub value: i32
 }
 
@@ -649,7 +572,7 @@ pub struct Poll {
     pub total_voter_count: i32,
     pub is_closed: bool,
     pub is_anonymous: bool,
-    pub typ: String,
+    pub typ: PollType,
     pub allows_multiple_answers: bool,
     pub correct_option_id: Option<i32>,
     pub explanation: Option<String>,
@@ -707,7 +630,7 @@ pub struct UserProfilePhotos {
 pub struct File {
     pub file_id: String,
     pub file_unique_id: String,
-    pub file_size: Option<String>,
+    pub file_size: Option<i64>,
     pub file_path: Option<String>
 }
 
@@ -726,7 +649,7 @@ pub struct KeyboardButton {
 }
 
 pub struct KeyboardButtonPollType {
-    pub typ: String
+    pub typ: Option<PollType>
 }
 
 pub struct ReplyKeyboardRemove {
@@ -745,6 +668,7 @@ pub struct InlineKeyboardButton {
     pub callback_data: Option<String>,
     pub switch_inline_query: Option<String>,
     pub switch_inline_query_current_chat: Option<String>,
+    pub callback_game: Option<CallbackGame>,
     pub pay: Option<bool>
 }
 
@@ -758,7 +682,7 @@ pub struct LoginUrl {
 pub struct CallbackQuery {
     pub id: String,
     pub from: User,
-    pub message: Option<Message>,
+    pub message: Option<Box<Message>>,
     pub inline_message_id: Option<String>,
     pub chat_instance: Option<String>,
     pub data: Option<String>,
@@ -786,33 +710,58 @@ pub struct ChatInviteLink {
     pub member_limit: Option<i32>
 }
 
-pub struct ChatMember {
+pub struct ChatMemberOwner {
     pub user: User,
-    pub status: String,
-    pub custom_title: Option<String>,
-    pub is_anonymous: Option<bool>,
-    pub can_be_edited: Option<bool>,
-    pub can_manage_chat: Option<bool>,
+    pub is_anonymous: bool,
+    pub custom_title: Option<String>
+}
+
+pub struct ChatMemberAdministrator {
+    pub user: User,
+    pub can_be_edited: bool,
+    pub is_anonymous: bool,
+    pub can_manage_chat: bool,
+    pub can_delete_messages: bool,
+    pub can_manage_voice_chats: bool,
+    pub can_restrict_members: bool,
+    pub can_promote_members: bool,
+    pub can_change_info: bool,
+    pub can_invite_users: bool,
     pub can_post_messages: Option<bool>,
     pub can_edit_messages: Option<bool>,
-    pub can_delete_messages: Option<bool>,
-    pub can_manage_voice_chats: Option<bool>,
-    pub can_restrict_members: Option<bool>,
-    pub can_promote_members: Option<bool>,
-    pub can_change_info: Option<bool>,
-    pub can_invite_users: Option<bool>,
     pub can_pin_messages: Option<bool>,
-    pub is_member: Option<bool>,
-    pub can_send_messages: Option<bool>,
-    pub can_send_media_messages: Option<bool>,
-    pub can_send_polls: Option<bool>,
-    pub can_send_other_messages: Option<bool>,
-    pub can_add_web_page_previews: Option<bool>,
-    pub until_date: Option<bool>
+    pub custom_title: Option<String>
+}
+
+pub struct ChatMemberMember {
+    pub user: User
+}
+
+pub struct ChatMemberRestricted {
+    pub user: User,
+    pub is_member: bool,
+    pub can_change_info: bool,
+    pub can_invite_users: bool,
+    pub can_pin_messages: bool,
+    pub can_send_messages: bool,
+    pub can_send_media_messages: bool,
+    pub can_send_polls: bool,
+    pub can_send_other_messages: bool,
+    pub can_add_web_page_previews: bool,
+    pub until_date: i32
+}
+
+pub struct ChatMemberLeft {
+    pub user: User
+}
+
+pub struct ChatMemberBanned {
+    pub user: User,
+    pub until_date: i32
 }
 
 pub struct ChatMemberUpdated {
-    pub chat: Chat,
+    pub chat: Box<Chat>,
     pub from: User,
     pub date: i32,
     pub old_chat_member: ChatMember,
@@ -842,15 +791,15 @@ pub struct BotCommand {
 }
 
 pub struct ResponseParameters {
-    pub migrate_to_chat_id: Option<i32>,
+    pub migrate_to_chat_id: Option<i64>,
     pub retry_after: Option<i32>
 }
 
 pub struct InputMedia {
-    pub typ: String,
-    pub media: String,
+    pub typ: InputMediaType,
+    pub media: InputFile,
     pub caption: Option<String>,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     pub caption_entities: Option<Vec<MessageEntity>>,
     pub width: Option<i32>,
     pub height: Option<i32>,
@@ -884,17 +833,714 @@ pub struct StickerSet {
 }
 
 pub struct MaskPosition {
-    pub point: String,
+    pub point: MaskPoint,
     pub x_shift: f64,
     pub y_shift: f64,
     pub scale: f64
+}
+
+pub struct InlineQuery {
+    pub id: String,
+    pub from: User,
+    pub query: String,
+    pub offset: String,
+    pub chat_type: Option<ChatType>,
+    pub location: Option<Location>
+}
+
+pub struct InlineQueryResultArticle {
+    pub id: String,
+    pub title: String,
+    pub input_message_content: InputMessageContent,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub url: Option<String>,
+    pub hide_url: Option<bool>,
+    pub description: Option<String>,
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<i32>,
+    pub thumb_height: Option<i32>
+}
+
+pub struct InlineQueryResultPhoto {
+    pub id: String,
+    pub photo_url: String,
+    pub thumb_url: String,
+    pub photo_width: Option<i32>,
+    pub photo_height: Option<i32>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultGif {
+    pub id: String,
+    pub gif_url: String,
+    pub gif_width: Option<i32>,
+    pub gif_height: Option<i32>,
+    pub gif_duration: Option<i32>,
+    pub thumb_url: String,
+    pub thumb_mime_type: Option<String>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultMpeg4Gif {
+    pub id: String,
+    pub mpeg4_url: String,
+    pub mpeg4_width: Option<i32>,
+    pub mpeg4_height: Option<i32>,
+    pub mpeg4_duration: Option<i32>,
+    pub thumb_url: String,
+    pub thumb_mime_type: Option<String>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultVideo {
+    pub id: String,
+    pub video_url: String,
+    pub mime_type: String,
+    pub thumb_url: String,
+    pub title: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub video_width: Option<i32>,
+    pub video_height: Option<i32>,
+    pub video_duration: Option<i32>,
+    pub description: Option<String>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultAudio {
+    pub id: String,
+    pub audio_url: String,
+    pub title: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub performer: Option<String>,
+    pub audio_duration: Option<i32>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultVoice {
+    pub id: String,
+    pub voice_url: String,
+    pub title: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub voice_duration: Option<i32>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultDocument {
+    pub id: String,
+    pub title: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub document_url: String,
+    pub mime_type: String,
+    pub description: Option<String>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>,
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<i32>,
+    pub thumb_height: Option<i32>
+}
+
+pub struct InlineQueryResultLocation {
+    pub id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub title: String,
+    pub horizontal_accuracy: Option<f64>,
+    pub live_period: Option<i32>,
+    pub heading: Option<i32>,
+    pub proximity_alert_radius: Option<i32>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>,
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<i32>,
+    pub thumb_height: Option<i32>
+}
+
+pub struct InlineQueryResultVenue {
+    pub id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub title: String,
+    pub address: String,
+    pub foursquare_id: Option<String>,
+    pub foursquare_type: Option<String>,
+    pub google_place_id: Option<String>,
+    pub google_place_type: Option<String>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>,
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<i32>,
+    pub thumb_height: Option<i32>
+}
+
+pub struct InlineQueryResultContact {
+    pub id: String,
+    pub phone_number: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub vcard: Option<String>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>,
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<i32>,
+    pub thumb_height: Option<i32>
+}
+
+pub struct InlineQueryResultGame {
+    pub id: String,
+    pub game_short_name: String,
+    pub reply_markup: Option<InlineKeyboardMarkup>
+}
+
+pub struct InlineQueryResultCachedPhoto {
+    pub id: String,
+    pub photo_file_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedGif {
+    pub id: String,
+    pub gif_file_id: String,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedMpeg4Gif {
+    pub id: String,
+    pub mpeg4_file_id: String,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedSticker {
+    pub id: String,
+    pub sticker_file_id: String,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedDocument {
+    pub id: String,
+    pub title: String,
+    pub document_file_id: String,
+    pub description: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedVideo {
+    pub id: String,
+    pub video_file_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedVoice {
+    pub id: String,
+    pub voice_file_id: String,
+    pub title: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InlineQueryResultCachedAudio {
+    pub id: String,
+    pub audio_file_id: String,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+    pub input_message_content: Option<InputMessageContent>
+}
+
+pub struct InputTextMessageContent {
+    pub message_text: String,
+    pub parse_mode: Option<ParseMode>,
+    pub entities: Option<Vec<MessageEntity>>,
+    pub disable_web_page_preview: Option<bool>
+}
+
+pub struct InputLocationMessageContent {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub horizontal_accuracy: Option<f64>,
+    pub live_period: Option<i32>,
+    pub heading: Option<i32>,
+    pub proximity_alert_radius: Option<i32>
+}
+
+pub struct InputVenueMessageContent {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub title: String,
+    pub address: String,
+    pub foursquare_id: Option<String>,
+    pub foursquare_type: Option<String>,
+    pub google_place_id: Option<String>,
+    pub google_place_type: Option<String>
+}
+
+pub struct InputContactMessageContent {
+    pub phone_number: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub vcard: Option<String>
+}
+
+pub struct InputInvoiceMessageContent {
+    pub title: String,
+    pub description: String,
+    pub payload: String,
+    pub provider_token: String,
+    pub currency: String,
+    pub prices: Vec<LabeledPrice>,
+    pub max_tip_amount: Option<i32>,
+    pub suggested_tip_amounts: Option<Vec<i32>>,
+    pub provider_data: Option<String>,
+    pub photo_url: Option<String>,
+    pub photo_size: Option<i32>,
+    pub photo_width: Option<i32>,
+    pub photo_height: Option<i32>,
+    pub need_name: Option<bool>,
+    pub need_phone_number: Option<bool>,
+    pub need_email: Option<bool>,
+    pub need_shipping_address: Option<bool>,
+    pub send_phone_number_to_provider: Option<bool>,
+    pub send_email_to_provider: Option<bool>,
+    pub is_flexible: Option<bool>
+}
+
+pub struct ChosenInlineResult {
+    pub result_id: String,
+    pub from: User,
+    pub location: Option<Location>,
+    pub inline_message_id: Option<String>,
+    pub query: String
+}
+
+pub struct LabeledPrice {
+    pub label: String,
+    pub amount: i32
+}
+
+pub struct Invoice {
+    pub title: String,
+    pub description: String,
+    pub start_parameter: String,
+    pub currency: String,
+    pub total_amount: i32
+}
+
+pub struct ShippingAddress {
+    pub country_code: String,
+    pub state: String,
+    pub city: String,
+    pub street_line1: String,
+    pub street_line2: String,
+    pub post_code: String
+}
+
+pub struct OrderInfo {
+    pub name: Option<String>,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub shipping_address: Option<ShippingAddress>
+}
+
+pub struct ShippingOption {
+    pub id: String,
+    pub title: String,
+    pub prices: Vec<LabeledPrice>
+}
+
+pub struct SuccessfulPayment {
+    pub currency: String,
+    pub total_amount: i32,
+    pub invoice_payload: String,
+    pub shipping_option_id: Option<String>,
+    pub order_info: Option<OrderInfo>,
+    pub telegram_payment_charge_id: String,
+    pub provider_payment_charge_id: String
+}
+
+pub struct ShippingQuery {
+    pub id: String,
+    pub from: User,
+    pub invoice_payload: String,
+    pub shipping_address: ShippingAddress
+}
+
+pub struct PreCheckoutQuery {
+    pub id: String,
+    pub from: User,
+    pub currency: String,
+    pub total_amount: i32,
+    pub invoice_payload: String,
+    pub shipping_option_id: Option<String>,
+    pub order_info: Option<OrderInfo>
+}
+
+pub struct PassportData {
+    pub data: Vec<EncryptedPassportElement>,
+    pub credentials: EncryptedCredentials
+}
+
+pub struct PassportFile {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub file_size: i32,
+    pub file_date: i32
+}
+
+pub struct EncryptedPassportElement {
+    pub typ: String,
+    pub data: Option<String>,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub files: Option<Vec<PassportFile>>,
+    pub front_side: Option<PassportFile>,
+    pub reverse_side: Option<PassportFile>,
+    pub selfie: Option<PassportFile>,
+    pub translation: Option<Vec<PassportFile>>,
+    pub hash: String
+}
+
+pub struct EncryptedCredentials {
+    pub data: String,
+    pub hash: String,
+    pub secret: String
+}
+
+pub struct Credentials {
+    pub secure_data: SecureData,
+    pub nonce: String
+}
+
+pub struct SecureData {
+    pub personal_details: Option<SecureValue>,
+    pub passport: Option<SecureValue>,
+    pub internal_passport: Option<SecureValue>,
+    pub driver_license: Option<SecureValue>,
+    pub identity_card: Option<SecureValue>,
+    pub address: Option<SecureValue>,
+    pub utility_bill: Option<SecureValue>,
+    pub bank_statement: Option<SecureValue>,
+    pub rental_agreement: Option<SecureValue>,
+    pub passport_registration: Option<SecureValue>,
+    pub temporary_registration: Option<SecureValue>
+}
+
+pub struct SecureValue {
+    pub data: Option<DataCredentials>,
+    pub front_side: Option<FileCredentials>,
+    pub reverse_side: Option<FileCredentials>,
+    pub selfie: Option<FileCredentials>,
+    pub translation: Option<Vec<FileCredentials>>,
+    pub files: Option<Vec<FileCredentials>>
+}
+
+pub struct DataCredentials {
+    pub data_hash: String,
+    pub secret: String
+}
+
+pub struct FileCredentials {
+    pub file_hash: String,
+    pub secret: String
+}
+
+pub struct PassportElementErrorDataField {
+    pub typ: String,
+    pub field_name: String,
+    pub data_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorFrontSide {
+    pub typ: String,
+    pub file_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorReverseSide {
+    pub typ: String,
+    pub file_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorSelfie {
+    pub typ: String,
+    pub file_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorFile {
+    pub typ: String,
+    pub file_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorFiles {
+    pub typ: String,
+    pub file_hashes: Vec<String>,
+    pub message: String
+}
+
+pub struct PassportElementErrorTranslationFile {
+    pub typ: String,
+    pub file_hash: String,
+    pub message: String
+}
+
+pub struct PassportElementErrorTranslationFiles {
+    pub typ: String,
+    pub file_hashes: Vec<String>,
+    pub message: String
+}
+
+pub struct PassportElementErrorUnspecified {
+    pub typ: String,
+    pub element_hash: String,
+    pub message: String
+}
+
+pub struct Game {
+    pub title: String,
+    pub description: String,
+    pub photo: Vec<PhotoSize>,
+    pub text: Option<String>,
+    pub text_entities: Option<Vec<MessageEntity>>,
+    pub animation: Option<Animation>
+}
+
+pub struct GameHighScore {
+    pub position: i32,
+    pub user: User,
+    pub score: i32
 }}
 
-add_functionality_empty! {
-    pub struct VoiceChatStarted {
+add_functionality! {
+pub struct VoiceChatStarted {}
+
+pub struct CallbackGame {}
+}
+
+add_functionality_enum! {
+pub enum ReplyMarkup {
+    InlineKeyboardMarkup(InlineKeyboardMarkup) if "inline_keyboard",
+    ReplyKeyboardMarkup(ReplyKeyboardMarkup) if "keyboard",
+    ReplyKeyboardRemove(ReplyKeyboardRemove) if "remove_keyboard",
+    ForceReply(ForceReply) if "force_reply"
+}
+
+pub enum InlineQueryResult {
+    Article(InlineQueryResultArticle) = "type": "article" if "id",
+    Photo(InlineQueryResultPhoto) = "type": "photo" if "photo_url",
+    Gif(InlineQueryResultGif) = "type": "gif" if "gif_url",
+    Mpeg4Gif(InlineQueryResultMpeg4Gif) = "type": "mpeg4_gif" if "mpeg4_url",
+    Video(InlineQueryResultVideo) = "type": "video" if "video_url",
+    Audio(InlineQueryResultAudio) = "type": "audio" if "audio_url",
+    Voice(InlineQueryResultVoice) = "type": "voice" if "voice_url",
+    Document(InlineQueryResultDocument) = "type": "document" if "document_url",
+    Location(InlineQueryResultLocation) = "type": "location" if "latitude",
+    Venue(InlineQueryResultVenue) = "type": "venue" if "address",
+    Contact(InlineQueryResultContact) = "type": "contact" if "phone_number",
+    Game(InlineQueryResultGame) = "type": "game" if "game_short_name",
+    CachedPhoto(InlineQueryResultCachedPhoto) = "type": "photo" if "photo_file_id",
+    CachedGif(InlineQueryResultCachedGif) = "type": "gif" if "gif_file_id",
+    CachedMpeg4Gif(InlineQueryResultCachedMpeg4Gif) = "type": "mpeg4_gif" if "mpeg4_file_id",
+    CachedSticker(InlineQueryResultCachedSticker) = "type": "sticker" if "sticker_file_id",
+    CachedDocument(InlineQueryResultCachedDocument) = "type": "document" if "document_file_id",
+    CachedVideo(InlineQueryResultCachedVideo) = "type": "video" if "video_file_id",
+    CachedVoice(InlineQueryResultCachedVoice) = "type": "voice" if "voice_file_id",
+    CachedAudio(InlineQueryResultCachedAudio) = "type": "audio" if "audio_file_id"
+}
+
+pub enum InputMessageContent {
+    Text(InputTextMessageContent) if "message_text",
+    Venue(InputVenueMessageContent) if "address",
+    Location(InputLocationMessageContent) if "latitude",
+    Contact(InputContactMessageContent) if "phone_number",
+    Invoice(InputInvoiceMessageContent) if "payload"
+}
+
+pub enum PassportElementError {
+    DataField(PassportElementErrorDataField) = "source": "data" if "field_name",
+    FrontSide(PassportElementErrorFrontSide) = "source": "front_side" if "file_hash",
+    ReverseSide(PassportElementErrorReverseSide) = "source": "reverse_side" if "file_hash",
+    Selfie(PassportElementErrorSelfie) = "source": "selfie" if "file_hash",
+    File(PassportElementErrorFile) = "source": "file" if "file_hash",
+    Files(PassportElementErrorFiles) = "source": "files" if "file_hashes",
+    TranslationFile(PassportElementErrorTranslationFile) = "source": "translation_file" if "file_hash",
+    TranslationFiles(PassportElementErrorTranslationFiles) = "source": "translation_files" if "file_hashes",
+    Unspecified(PassportElementErrorUnspecified) = "source": "unspecified" if "element_hash"
+}
+
+pub enum ChatMember {
+    Owner(ChatMemberOwner) = "status": "creator" if "user",
+    Administrator(ChatMemberAdministrator) = "status": "administrator" if "user",
+    Member(ChatMemberMember) = "status": "member" if "user",
+    Restricted(ChatMemberRestricted) = "status": "restricted" if "user",
+    Left(ChatMemberLeft) = "status": "left" if "user",
+    Banned(ChatMemberBanned) = "status": "kicked" if "user";
+    Unknown
 }}
 
+/// Permission is anything a chat member may be allowed to do, see `ChatMember::can`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    ManageChat,
+    PostMessages,
+    EditMessages,
+    DeleteMessages,
+    ManageVoiceChats,
+    RestrictMembers,
+    PromoteMembers,
+    ChangeInfo,
+    InviteUsers,
+    PinMessages,
+    SendMessages,
+    SendMediaMessages,
+    SendPolls,
+    SendOtherMessages,
+    AddWebPagePreviews
+}
+
+impl Permission {
+    fn is_send(self) -> bool {
+        matches!(self, Permission::SendMessages | Permission::SendMediaMessages | Permission::SendPolls
+            | Permission::SendOtherMessages | Permission::AddWebPagePreviews)
+    }
+}
+
+impl ChatMember {
+    /// user returns the user of the member, None for statuses unknown to this crate
+    pub fn user(&self) -> Option<&User> {
+        match self {
+            ChatMember::Owner(m) => Some(&m.user),
+            ChatMember::Administrator(m) => Some(&m.user),
+            ChatMember::Member(m) => Some(&m.user),
+            ChatMember::Restricted(m) => Some(&m.user),
+            ChatMember::Left(m) => Some(&m.user),
+            ChatMember::Banned(m) => Some(&m.user),
+            ChatMember::Unknown(_) => None,
+        }
+    }
+
+    pub fn status(&self) -> ChatMemberStatus {
+        match self {
+            ChatMember::Owner(_) => ChatMemberStatus::Creator,
+            ChatMember::Administrator(_) => ChatMemberStatus::Administrator,
+            ChatMember::Member(_) => ChatMemberStatus::Member,
+            ChatMember::Restricted(_) => ChatMemberStatus::Restricted,
+            ChatMember::Left(_) => ChatMemberStatus::Left,
+            ChatMember::Banned(_) => ChatMemberStatus::Kicked,
+            ChatMember::Unknown(data) => ChatMemberStatus::from(data["status"].as_str().unwrap_or_default()),
+        }
+    }
+
+    /// is_admin checks whether the member is the owner or an administrator of the chat
+    pub fn is_admin(&self) -> bool {
+        matches!(self, ChatMember::Owner(_) | ChatMember::Administrator(_))
+    }
+
+    /// is_member checks whether the user is currently in the chat, restricted users included
+    pub fn is_member(&self) -> bool {
+        match self {
+            ChatMember::Owner(_) | ChatMember::Administrator(_) | ChatMember::Member(_) => true,
+            ChatMember::Restricted(m) => m.is_member,
+            ChatMember::Left(_) | ChatMember::Banned(_) | ChatMember::Unknown(_) => false,
+        }
+    }
+
+    /// can checks whether the member has `permission` by their own rights.
+    /// Plain members are allowed to send messages, the default permissions of the chat are not taken into account.
+    pub fn can(&self, permission: Permission) -> bool {
+        match self {
+            ChatMember::Owner(_) => true,
+            ChatMember::Administrator(m) => match permission {
+                Permission::ManageChat => m.can_manage_chat,
+                Permission::PostMessages => m.can_post_messages.unwrap_or(false),
+                Permission::EditMessages => m.can_edit_messages.unwrap_or(false),
+                Permission::DeleteMessages => m.can_delete_messages,
+                Permission::ManageVoiceChats => m.can_manage_voice_chats,
+                Permission::RestrictMembers => m.can_restrict_members,
+                Permission::PromoteMembers => m.can_promote_members,
+                Permission::ChangeInfo => m.can_change_info,
+                Permission::InviteUsers => m.can_invite_users,
+                Permission::PinMessages => m.can_pin_messages.unwrap_or(false),
+                _ => true
+            },
+            ChatMember::Member(_) => permission.is_send(),
+            ChatMember::Restricted(m) => match permission {
+                Permission::ChangeInfo => m.can_change_info,
+                Permission::InviteUsers => m.can_invite_users,
+                Permission::PinMessages => m.can_pin_messages,
+                Permission::SendMessages => m.can_send_messages,
+                Permission::SendMediaMessages => m.can_send_media_messages,
+                Permission::SendPolls => m.can_send_polls,
+                Permission::SendOtherMessages => m.can_send_other_messages,
+                Permission::AddWebPagePreviews => m.can_add_web_page_previews,
+                _ => false
+            },
+            ChatMember::Left(_) | ChatMember::Banned(_) | ChatMember::Unknown(_) => false,
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::needless_late_init)]
 mod tests {
     use super::*;
 
@@ -934,7 +1580,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
+    #[should_panic(expected = "can't decode User: missing field `is_bot`")]
     fn test_invalid_user() {
         let json_user = json::parse("{\"id\":1234,\"first_name\":\"iamgroot\"}");
         let _user;
@@ -1010,7 +1656,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
+    #[should_panic(expected = "can't decode MessageEntity: missing field `offset`")]
     fn test_invalid_me() {
         let json_me = json::parse("{\"type\":\"cashtag\",\"length\":69}");
         let _me;
@@ -1049,6 +1695,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_input_file() {
+        assert!(matches!(InputFile::from("https://example.com/cat.jpg"), InputFile::Url(_)));
+        assert!(matches!(InputFile::from("AgACAgIAAxkBAAI"), InputFile::FileId(_)));
+        let file = InputFile::Path(PathBuf::from("/tmp/pictures/cat.jpg"));
+        assert!(file.is_upload());
+        assert_eq!(format!("{}", file), "attach://cat.jpg");
+    }
+
+    #[test]
+    fn test_input_media_memory() {
+        let mut media = InputMedia::empty();
+        media.typ = InputMediaType::Photo;
+        media.media = InputFile::Memory { file_name: "cat.jpg".to_string(), data: vec![1, 2, 3] };
+        assert_eq!(format!("{}", media.to_json()), r#"{"type":"photo","media":"attach://cat.jpg"}"#);
+    }
+
     #[test]
     fn test_chat_photo() {
         let reference = r#"{"small_file_id":"1","small_file_unique_id":"1234","big_file_id":"2","big_file_unique_id":"2345"}"#;
@@ -1067,12 +1730,40 @@ mod tests {
 
     #[test]
     fn test_chat_member() {
-        let reference = r#"{"user":{"id":1234,"is_bot":true,"first_name":"groot"},"status":"creator"}"#;
+        let reference = r#"{"status":"creator","user":{"id":1234,"is_bot":true,"first_name":"groot"},"is_anonymous":false}"#;
         expand_basic_test!{
             fn run_test(ChatMember, reference)
         }
     }
 
+    #[test]
+    fn test_chat_member_permissions() {
+        let reference = r#"[{"status":"administrator","user":{"id":1,"is_bot":false,"first_name":"admin"},"can_be_edited":false,"is_anonymous":false,"can_manage_chat":true,"can_delete_messages":true,"can_manage_voice_chats":false,"can_restrict_members":true,"can_promote_members":false,"can_change_info":false,"can_invite_users":true,"can_pin_messages":true},{"status":"restricted","user":{"id":2,"is_bot":false,"first_name":"muted"},"is_member":true,"can_change_info":false,"can_invite_users":false,"can_pin_messages":false,"can_send_messages":false,"can_send_media_messages":false,"can_send_polls":false,"can_send_other_messages":false,"can_add_web_page_previews":false,"until_date":1700000000}]"#;
+        let members: Vec<ChatMember> = serde_json::from_str(reference).unwrap();
+        assert!(members[0].is_admin());
+        assert!(members[0].can(Permission::RestrictMembers));
+        assert!(members[0].can(Permission::PinMessages));
+        assert!(!members[0].can(Permission::PromoteMembers));
+        assert!(!members[0].can(Permission::PostMessages));
+        assert!(!members[1].is_admin());
+        assert!(members[1].is_member());
+        assert!(!members[1].can(Permission::SendMessages));
+        assert_eq!(members[1].user().unwrap().first_name, "muted");
+        assert_eq!(members[1].status(), ChatMemberStatus::Restricted);
+        assert!(matches!(&members[1], ChatMember::Restricted(m) if m.until_date == 1700000000 && m.extra.is_empty()));
+    }
+
+    #[test]
+    fn test_unknown_chat_member() {
+        let reference = r#"{"status":"guest","user":{"id":1234,"is_bot":false,"first_name":"groot"}}"#;
+        let member: ChatMember = serde_json::from_str(reference).unwrap();
+        assert!(matches!(member, ChatMember::Unknown(_)));
+        assert_eq!(member.status(), ChatMemberStatus::Unknown("guest".to_string()));
+        assert!(member.user().is_none());
+        assert!(!member.is_member());
+        assert_eq!(serde_json::to_string(&member).unwrap(), reference);
+    }
+
     #[test]
     fn test_chat_permissions() {
         let reference = r#"{"can_send_messages":true}"#;
@@ -1169,6 +1860,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_typed_enums() {
+        let dice: Dice = serde_json::from_str(r#"{"emoji":"🎯","value":6}"#).unwrap();
+        assert_eq!(dice.emoji, DiceEmoji::Darts);
+        let chat: Chat = serde_json::from_str(r#"{"id":-1001234,"type":"supergroup"}"#).unwrap();
+        assert_eq!(chat.typ, ChatType::Supergroup);
+        let position: MaskPosition = serde_json::from_str(r#"{"point":"eyes","x_shift":0.0,"y_shift":0.0,"scale":1.0}"#).unwrap();
+        assert_eq!(position.point, MaskPoint::Eyes);
+        assert_eq!(format!("{}", ChatAction::UploadVideoNote), "upload_video_note");
+    }
+
     #[test]
     fn test_poll_option() {
         let reference = r#"{"text":"nein","voter_count":3}"#;
@@ -1281,6 +1983,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_full_file() {
+        let reference = r#"{"file_id":"1","file_unique_id":"1234","file_size":2048,"file_path":"photos/file_0.jpg"}"#;
+        expand_basic_test!{
+            fn run_test(File, reference)
+        }
+    }
+
     #[test]
     fn test_reply_keyboard_markup() {
         let reference = r#"{"keyboard":[[{"text":"quiz1"},{"text":"quiz2"}],[{"text":"quiz3"},{"text":"quiz4"}]]}"#;
@@ -1303,6 +2013,34 @@ mod tests {
         expand_basic_test!{
             fn run_test(KeyboardButtonPollType, reference)
         }
+        // without a type, the user may create polls of any type
+        let reference = r#"{}"#;
+        expand_basic_test!{
+            fn run_test(KeyboardButtonPollType, reference)
+        }
+        assert!(KeyboardButtonPollType::from_json(json::parse(reference).unwrap()).typ.is_none());
+    }
+
+    #[test]
+    fn test_serde() {
+        let reference = r#"{"inline_keyboard":[[{"text":"yes","callback_data":"1"}]]}"#;
+        let markup: ReplyMarkup = serde_json::from_str(reference).unwrap();
+        assert!(matches!(markup, ReplyMarkup::InlineKeyboardMarkup(_)));
+        assert_eq!(serde_json::to_string(&markup).unwrap(), reference);
+        let entity: MessageEntity = serde_json::from_str(r#"{"type":"text_link","offset":0,"length":4,"url":"a"}"#).unwrap();
+        assert!(matches!(entity.typ, MessageEntityType::TextLink));
+        assert!(serde_json::from_str::<MessageEntity>(r#"{"type":"cashtag","length":69}"#).is_err());
+    }
+
+    #[test]
+    fn test_unknown_values() {
+        let reference = r#"{"type":"spoiler","offset":0,"length":4,"custom_emoji_id":"1234"}"#;
+        let entity: MessageEntity = serde_json::from_str(reference).unwrap();
+        assert_eq!(entity.typ, MessageEntityType::Unknown("spoiler".to_string()));
+        assert_eq!(entity.extra["custom_emoji_id"], "1234");
+        assert_eq!(serde_json::to_string(&entity).unwrap(), reference);
+        assert_eq!(format!("{}", entity), "type: spoiler; offset: 0; length: 4; custom_emoji_id: 1234");
+        assert_eq!(ParseMode::from("HTML"), ParseMode::Html);
     }
 
     #[test]
@@ -1371,7 +2109,7 @@ mod tests {
 
     #[test]
     fn test_chat_member_updated() {
-        let reference = r#"{"chat":{"id":1234,"type":"private"},"from":{"id":1234,"is_bot":true,"first_name":"itsme"},"date":12,"old_chat_member":{"user":{"id":1234,"is_bot":true,"first_name":"groot"},"status":"creator"},"new_chat_member":{"user":{"id":1234,"is_bot":true,"first_name":"root"},"status":"creator"}}"#;
+        let reference = r#"{"chat":{"id":1234,"type":"private"},"from":{"id":1234,"is_bot":true,"first_name":"itsme"},"date":12,"old_chat_member":{"status":"member","user":{"id":1234,"is_bot":true,"first_name":"groot"}},"new_chat_member":{"status":"kicked","user":{"id":1234,"is_bot":true,"first_name":"root"},"until_date":0}}"#;
         expand_basic_test!{
             fn run_test(ChatMemberUpdated, reference)
         }
@@ -1424,4 +2162,147 @@ mod tests {
             fn run_test(Update, reference)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_webhook_info() {
+        let reference = r#"{"url":"https://example.com/hook","has_custom_certificate":false,"pending_update_count":3,"max_connections":40,"allowed_updates":["message","callback_query"]}"#;
+        expand_basic_test!{
+            fn run_test(WebhookInfo, reference)
+        }
+    }
+
+    #[test]
+    fn test_inline_query() {
+        let reference = r#"{"id":"1","from":{"id":1234,"is_bot":false,"first_name":"groot"},"query":"cats","offset":"","chat_type":"private"}"#;
+        expand_basic_test!{
+            fn run_test(InlineQuery, reference)
+        }
+    }
+
+    #[test]
+    fn test_chosen_inline_result() {
+        let reference = r#"{"result_id":"1","from":{"id":1234,"is_bot":false,"first_name":"groot"},"inline_message_id":"42","query":"cats"}"#;
+        expand_basic_test!{
+            fn run_test(ChosenInlineResult, reference)
+        }
+    }
+
+    #[test]
+    fn test_inline_query_result_article() {
+        let reference = r#"{"type":"article","id":"1","title":"cats","input_message_content":{"message_text":"cats are great","parse_mode":"HTML"},"description":"about cats"}"#;
+        expand_basic_test!{
+            fn run_test(InlineQueryResult, reference)
+        }
+    }
+
+    #[test]
+    fn test_inline_query_result_cached() {
+        let reference = r#"{"type":"photo","id":"2","photo_file_id":"AgACAgIAAxkBAAI","input_message_content":{"latitude":52.5,"longitude":13.4}}"#;
+        let result = InlineQueryResult::from_json(json::parse(reference).unwrap());
+        match &result {
+            InlineQueryResult::CachedPhoto(p) => assert!(matches!(p.input_message_content, Some(InputMessageContent::Location(_)))),
+            _ => panic!("expected a cached photo")
+        }
+        assert_eq!(format!("{}", result.to_json()), reference);
+    }
+
+    #[test]
+    fn test_update_inline_query() {
+        let reference = r#"{"update_id":10,"inline_query":{"id":"1","from":{"id":1234,"is_bot":false,"first_name":"groot"},"query":"cats","offset":""}}"#;
+        let update = Update::from_json(json::parse(reference).unwrap());
+        assert_eq!(update.inline_query.unwrap().query, "cats");
+    }
+
+    #[test]
+    fn test_shipping_option() {
+        let reference = r#"{"id":"dhl","title":"DHL","prices":[{"label":"Shipping","amount":499},{"label":"Insurance","amount":100}]}"#;
+        expand_basic_test!{
+            fn run_test(ShippingOption, reference)
+        }
+    }
+
+    #[test]
+    fn test_shipping_query() {
+        let reference = r#"{"id":"1","from":{"id":1234,"is_bot":false,"first_name":"groot"},"invoice_payload":"order-42","shipping_address":{"country_code":"DE","state":"","city":"Berlin","street_line1":"Street 1","street_line2":"","post_code":"10115"}}"#;
+        expand_basic_test!{
+            fn run_test(ShippingQuery, reference)
+        }
+    }
+
+    #[test]
+    fn test_pre_checkout_query() {
+        let reference = r#"{"id":"1","from":{"id":1234,"is_bot":false,"first_name":"groot"},"currency":"EUR","total_amount":1099,"invoice_payload":"order-42","order_info":{"name":"groot","email":"groot@example.org"}}"#;
+        expand_basic_test!{
+            fn run_test(PreCheckoutQuery, reference)
+        }
+    }
+
+    #[test]
+    fn test_successful_payment_message() {
+        let reference = r#"{"message_id":10,"date":5,"chat":{"id":12,"type":"private"},"successful_payment":{"currency":"EUR","total_amount":1099,"invoice_payload":"order-42","telegram_payment_charge_id":"t1","provider_payment_charge_id":"p1"}}"#;
+        let message = Message::from_json(json::parse(reference).unwrap());
+        assert_eq!(message.successful_payment.unwrap().invoice_payload, "order-42");
+    }
+
+    #[test]
+    fn test_game() {
+        let reference = r#"{"title":"snake","description":"eat the apples","photo":[{"file_id":"1","file_unique_id":"1234","width":320,"height":180}]}"#;
+        expand_basic_test!{
+            fn run_test(Game, reference)
+        }
+    }
+
+    #[test]
+    fn test_game_high_score() {
+        let reference = r#"{"position":1,"user":{"id":1234,"is_bot":false,"first_name":"groot"},"score":9000}"#;
+        expand_basic_test!{
+            fn run_test(GameHighScore, reference)
+        }
+    }
+
+    #[test]
+    fn test_callback_game_button() {
+        let reference = r#"{"text":"play","callback_game":{}}"#;
+        let button = InlineKeyboardButton::from_json(json::parse(reference).unwrap());
+        assert!(button.callback_game.is_some());
+        assert_eq!(format!("{}", button.to_json()), reference);
+    }
+
+    #[test]
+    fn test_passport_data() {
+        let reference = r#"{"data":[{"type":"personal_details","data":"ZGF0YQ==","hash":"aGFzaA=="},{"type":"phone_number","phone_number":"491234","hash":"aGFzaDI="}],"credentials":{"data":"ZGF0YQ==","hash":"aGFzaA==","secret":"c2VjcmV0"}}"#;
+        expand_basic_test!{
+            fn run_test(PassportData, reference)
+        }
+    }
+
+    #[test]
+    fn test_passport_element_error() {
+        let reference = r#"{"source":"files","type":"utility_bill","file_hashes":["aGFzaA=="],"message":"scan is blurry"}"#;
+        let error = PassportElementError::from_json(json::parse(reference).unwrap());
+        assert!(matches!(error, PassportElementError::Files(_)));
+        assert_eq!(format!("{}", error.to_json()), reference);
+    }
+
+    #[test]
+    fn test_reply_markup() {
+        let reference = r#"{"remove_keyboard":true,"selective":true}"#;
+        let markup = ReplyMarkup::from_json(json::parse(reference).unwrap());
+        assert!(matches!(markup, ReplyMarkup::ReplyKeyboardRemove(_)));
+        assert_eq!(format!("{}", markup.to_json()), reference);
+        let mut force_reply = ForceReply::empty();
+        force_reply.force_reply = true;
+        assert_eq!(format!("{}", ReplyMarkup::from(force_reply).to_json()), r#"{"force_reply":true}"#);
+    }
+
+    #[test]
+    fn test_json_conversion() {
+        let reference = r#"{"id":-1001234567890,"big":18446744073709551615,"price":4.99,"exp":1e3,"short":"a","long":"longer than a short string","list":[true,null,{"b":1}]}"#;
+        let value = json_to_value(json::parse(reference).unwrap());
+        assert_eq!(value, serde_json::json!({
+            "id": -1001234567890i64, "big": u64::MAX, "price": 4.99, "exp": 1000, "short": "a",
+            "long": "longer than a short string", "list": [true, null, { "b": 1 }]
+        }));
+        assert_eq!(value_to_json(value), json::parse(reference).unwrap());
+    }
+}
@@ -0,0 +1,281 @@
+//! This module contains offset stores, which keep the polling offset across restarts</br>
+//! With a store, the offset is only committed once an update was acknowledged, e.g.:
+//! ```ignore
+//! let mut bot = Bot::new("your_bot_api_key".to_string()).with_offset_store(FileOffsetStore::new("offset"));
+//! let (tx, rx) = mpsc::channel();
+//! bot.start_polling(tx);
+//! dispatcher.run(rx, &bot.ack_handle())?;
+//! ```
+//! Updates which were received but not acknowledged before a crash are received again after the restart,
+//! so every update is handled at least once. Updates have to be acknowledged in the order they were received.
+//! Updates, which can't be decoded, are acknowledged together with the update received right before them.
+
+use crate::error::TelegramError;
+use crate::objects::Update;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// OffsetStore keeps the offset of the first update, which was not acknowledged yet
+pub trait OffsetStore: Send {
+    /// load returns the stored offset, 0 if nothing was stored yet
+    fn load(&mut self) -> Result<i64, TelegramError>;
+    /// commit stores `offset` after all updates before it were acknowledged
+    fn commit(&mut self, offset: i64) -> Result<(), TelegramError>;
+}
+
+/// MemoryOffsetStore keeps the offset in memory, it is lost on restart
+#[derive(Debug, Default)]
+pub struct MemoryOffsetStore {
+    offset: i64,
+}
+
+impl MemoryOffsetStore {
+    pub fn new() -> MemoryOffsetStore {
+        MemoryOffsetStore::default()
+    }
+}
+
+impl OffsetStore for MemoryOffsetStore {
+    fn load(&mut self) -> Result<i64, TelegramError> {
+        Ok(self.offset)
+    }
+
+    fn commit(&mut self, offset: i64) -> Result<(), TelegramError> {
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+/// FileOffsetStore keeps the offset as text in a file, which is created on the first commit
+#[derive(Debug)]
+pub struct FileOffsetStore {
+    path: PathBuf,
+}
+
+impl FileOffsetStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> FileOffsetStore {
+        FileOffsetStore { path: path.as_ref().to_path_buf() }
+    }
+}
+
+impl OffsetStore for FileOffsetStore {
+    fn load(&mut self) -> Result<i64, TelegramError> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let text = fs::read_to_string(&self.path)?;
+        // a crash while the file was created can leave it empty
+        if text.trim().is_empty() {
+            return Ok(0);
+        }
+        text.trim().parse().map_err(|_| TelegramError::Decode(format!("invalid offset \"{}\"", text.trim())))
+    }
+
+    /// commit replaces the file with write_atomically, so it is never left half written
+    fn commit(&mut self, offset: i64) -> Result<(), TelegramError> {
+        write_atomically(&self.path, offset.to_string().as_bytes())?;
+        Ok(())
+    }
+}
+
+/// write_atomically writes and syncs "<path>.tmp" first and renames it to `path` afterwards,
+/// the directory is synced as well, so the renamed file survives a crash
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = File::create(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    sync_dir(path)
+}
+
+/// sync_dir syncs the directory containing `path`, directories can't be opened for that on Windows
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => File::open(".")?.sync_all()
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+struct Committed {
+    store: Box<dyn OffsetStore>,
+    offset: Option<i64>,
+    /// undecodable updates by the id of the update received right before them
+    skipped: HashMap<i64, i64>,
+}
+
+/// AckedOffset shares an offset store between clones of a bot, so updates can be acknowledged
+/// by another thread than the one polling
+#[derive(Clone)]
+pub(crate) struct AckedOffset {
+    inner: Arc<(Mutex<Committed>, Condvar)>,
+}
+
+impl AckedOffset {
+    pub(crate) fn new<S: OffsetStore + 'static>(store: S) -> AckedOffset {
+        AckedOffset { inner: Arc::new((Mutex::new(Committed { store: Box::new(store), offset: None, skipped: HashMap::new() }), Condvar::new())) }
+    }
+
+    /// load returns the committed offset, it is only read from the store once
+    pub(crate) fn load(&self) -> Result<i64, TelegramError> {
+        let mut committed = self.inner.0.lock().unwrap();
+        load(&mut committed)
+    }
+
+    /// ack commits the offset after `update_id`, acknowledging an update twice does nothing
+    pub(crate) fn ack(&self, update_id: i64) -> Result<(), TelegramError> {
+        let mut committed = self.inner.0.lock().unwrap();
+        if update_id >= load(&mut committed)? {
+            commit(&mut committed, update_id)?;
+            self.inner.1.notify_all();
+        }
+        Ok(())
+    }
+
+    /// skip acknowledges the undecodable update `update_id` together with the update `after`, which was received
+    /// right before it. Without `after`, it was the first update received and is acknowledged right away.
+    pub(crate) fn skip(&self, update_id: i64, after: Option<i64>) -> Result<(), TelegramError> {
+        let mut committed = self.inner.0.lock().unwrap();
+        let offset = load(&mut committed)?;
+        match after {
+            Some(after) if after >= offset => {
+                committed.skipped.insert(after, update_id);
+            },
+            _ if update_id >= offset => {
+                commit(&mut committed, update_id)?;
+                self.inner.1.notify_all();
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// is_acked returns whether all updates before `offset` were acknowledged
+    #[cfg(feature = "async")]
+    pub(crate) fn is_acked(&self, offset: i64) -> bool {
+        self.inner.0.lock().unwrap().offset.map(|o| o >= offset).unwrap_or(false)
+    }
+
+    /// wait_for blocks until all updates before `offset` were acknowledged or `running` is false
+    pub(crate) fn wait_for(&self, offset: i64, running: &AtomicBool) {
+        let mut committed = self.inner.0.lock().unwrap();
+        while committed.offset.map(|o| o < offset).unwrap_or(true) && running.load(Ordering::SeqCst) {
+            committed = self.inner.1.wait_timeout(committed, Duration::from_millis(100)).unwrap().0;
+        }
+    }
+}
+
+/// AckHandle acknowledges updates of a bot from wherever they are handled, e.g. in `Dispatcher::run`.
+/// Without offset store acknowledging does nothing.
+#[derive(Clone, Default)]
+pub struct AckHandle {
+    offset_store: Option<AckedOffset>,
+}
+
+impl AckHandle {
+    pub(crate) fn new(offset_store: Option<AckedOffset>) -> AckHandle {
+        AckHandle { offset_store }
+    }
+
+    /// ack commits the offset after `update` to the offset store
+    pub fn ack(&self, update: &Update) -> Result<(), TelegramError> {
+        match &self.offset_store {
+            Some(offset_store) => offset_store.ack(update.update_id),
+            None => Ok(())
+        }
+    }
+}
+
+/// commit stores the offset after `update_id` and the skipped updates right after it
+fn commit(committed: &mut Committed, mut update_id: i64) -> Result<(), TelegramError> {
+    while let Some(skipped) = committed.skipped.remove(&update_id) {
+        update_id = skipped;
+    }
+    committed.store.commit(update_id + 1)?;
+    committed.offset = Some(update_id + 1);
+    Ok(())
+}
+
+fn load(committed: &mut Committed) -> Result<i64, TelegramError> {
+    match committed.offset {
+        Some(offset) => Ok(offset),
+        None => {
+            let offset = committed.store.load()?;
+            committed.offset = Some(offset);
+            Ok(offset)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_file_offset_store() {
+        let path = std::env::temp_dir().join(format!("telegram-api-rs-offset-{}", std::process::id()));
+        let mut store = FileOffsetStore::new(&path);
+        assert_eq!(store.load().unwrap(), 0);
+        store.commit(1234).unwrap();
+        assert_eq!(FileOffsetStore::new(&path).load().unwrap(), 1234);
+        fs::write(&path, "").unwrap();
+        assert_eq!(store.load().unwrap(), 0);
+        fs::write(&path, "invalid").unwrap();
+        assert!(matches!(store.load(), Err(TelegramError::Decode(_))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_temporary_file_name() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("telegram-api-rs-state-{}.offset", std::process::id()));
+        let other = dir.join(format!("telegram-api-rs-state-{}.tmp", std::process::id()));
+        fs::write(&other, "other").unwrap();
+        FileOffsetStore::new(&path).commit(5).unwrap();
+        assert_eq!(fs::read_to_string(&other).unwrap(), "other");
+        assert!(!dir.join(format!("telegram-api-rs-state-{}.offset.tmp", std::process::id())).exists());
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&other).unwrap();
+    }
+
+    #[test]
+    fn test_ack() {
+        let offset = AckedOffset::new(MemoryOffsetStore::new());
+        assert_eq!(offset.load().unwrap(), 0);
+        offset.ack(10).unwrap();
+        offset.ack(5).unwrap();
+        assert_eq!(offset.load().unwrap(), 11);
+        let acked = offset.clone();
+        let handle = thread::spawn(move || acked.ack(12).unwrap());
+        offset.wait_for(13, &AtomicBool::new(true));
+        handle.join().unwrap();
+        assert_eq!(offset.load().unwrap(), 13);
+    }
+
+    #[test]
+    fn test_skip() {
+        let offset = AckedOffset::new(MemoryOffsetStore::new());
+        offset.skip(1, None).unwrap();
+        assert_eq!(offset.load().unwrap(), 2);
+        offset.skip(4, Some(3)).unwrap();
+        offset.skip(5, Some(4)).unwrap();
+        offset.ack(2).unwrap();
+        assert_eq!(offset.load().unwrap(), 3);
+        offset.ack(3).unwrap();
+        assert_eq!(offset.load().unwrap(), 6);
+    }
+}
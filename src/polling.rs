@@ -0,0 +1,165 @@
+//! This module contains the configuration for long polling and the handle to stop it</br>
+//! By default, polling waits up to 30 seconds for new updates, e.g.:
+//! ```ignore
+//! let (tx, rx) = mpsc::channel();
+//! let config = PollingConfig { allowed_updates: Some(vec!["message".to_string()]), ..PollingConfig::default() };
+//! let polling = bot.start_polling_with_config(config, tx);
+//! let received = rx.recv().unwrap();
+//! polling.stop();
+//! ```
+//! Errors while fetching updates are passed to `on_error`. Polling goes on after a backoff, unless the key
+//! is not valid anymore (401) or another instance is polling or a webhook is set (409).
+//! Such an error stops polling and is returned by `PollingHandle::stop`.
+
+use crate::error::TelegramError;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// PollingErrorHandler is called with every error while fetching updates
+pub type PollingErrorHandler = Arc<dyn Fn(&TelegramError) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct PollingConfig {
+    /// how many seconds Telegram keeps a request open while there are no updates
+    pub timeout: i32,
+    /// how many updates are received at most per request
+    pub limit: Option<i32>,
+    /// which kinds of updates are received, all but chat_member updates if not set
+    pub allowed_updates: Option<Vec<String>>,
+    /// how long to wait after a failed request, doubled for every further failure
+    pub error_backoff: Duration,
+    /// how long to wait after a failed request at most
+    pub max_error_backoff: Duration,
+    /// called with every error while fetching updates, errors are skipped without any notice if not set
+    pub on_error: Option<PollingErrorHandler>,
+}
+
+impl fmt::Debug for PollingConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollingConfig")
+            .field("timeout", &self.timeout)
+            .field("limit", &self.limit)
+            .field("allowed_updates", &self.allowed_updates)
+            .field("error_backoff", &self.error_backoff)
+            .field("max_error_backoff", &self.max_error_backoff)
+            .field("on_error", &self.on_error.as_ref().map(|_| "Fn(&TelegramError)"))
+            .finish()
+    }
+}
+
+impl PartialEq for PollingConfig {
+    fn eq(&self, other: &Self) -> bool {
+        let same_handler = match (&self.on_error, &other.on_error) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none()
+        };
+        self.timeout == other.timeout && self.limit == other.limit && self.allowed_updates == other.allowed_updates
+            && self.error_backoff == other.error_backoff && self.max_error_backoff == other.max_error_backoff && same_handler
+    }
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        PollingConfig {
+            timeout: 30,
+            limit: None,
+            allowed_updates: None,
+            error_backoff: Duration::from_secs(1),
+            max_error_backoff: Duration::from_secs(60),
+            on_error: None,
+        }
+    }
+}
+
+impl PollingConfig {
+    /// backoff returns how long to wait after `failures` failed requests in a row
+    pub(crate) fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.error_backoff.saturating_mul(factor).min(self.max_error_backoff)
+    }
+
+    /// report passes `error` to `on_error` and returns whether polling has to stop because of it
+    pub(crate) fn report(&self, error: &TelegramError) -> bool {
+        if let Some(on_error) = &self.on_error {
+            on_error(error);
+        }
+        matches!(error, TelegramError::Api { error_code: 401, .. } | TelegramError::Api { error_code: 409, .. })
+    }
+}
+
+/// PollingHandle belongs to a polling thread, polling goes on until `stop` is called,
+/// the receiver of the updates is dropped or an error makes polling impossible
+pub struct PollingHandle {
+    running: Arc<AtomicBool>,
+    thread: thread::JoinHandle<Option<TelegramError>>,
+}
+
+impl PollingHandle {
+    pub(crate) fn new(running: Arc<AtomicBool>, thread: thread::JoinHandle<Option<TelegramError>>) -> PollingHandle {
+        PollingHandle { running, thread }
+    }
+
+    /// is_running checks whether the polling thread is still running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst) && !self.thread.is_finished()
+    }
+
+    /// stop lets the polling thread finish and waits for it, which takes until the current request
+    /// returns, i.e. up to `timeout` seconds. Updates received by this request are still sent.
+    /// It returns the error, which stopped polling before, if there was one.
+    pub fn stop(self) -> Option<TelegramError> {
+        self.running.store(false, Ordering::SeqCst);
+        self.thread.join().ok().flatten()
+    }
+}
+
+/// sleep_while_running sleeps for `duration`, but returns early once `running` is false
+pub(crate) fn sleep_while_running(duration: Duration, running: &AtomicBool) {
+    let step = Duration::from_millis(100);
+    let mut slept = Duration::from_secs(0);
+    while slept < duration && running.load(Ordering::SeqCst) {
+        let next = step.min(duration - slept);
+        thread::sleep(next);
+        slept += next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let config = PollingConfig { max_error_backoff: Duration::from_secs(5), ..PollingConfig::default() };
+        assert_eq!(config.backoff(1), Duration::from_secs(1));
+        assert_eq!(config.backoff(2), Duration::from_secs(2));
+        assert_eq!(config.backoff(3), Duration::from_secs(4));
+        assert_eq!(config.backoff(4), Duration::from_secs(5));
+        assert_eq!(config.backoff(100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_report() {
+        let reported = Arc::new(std::sync::Mutex::new(vec![]));
+        let r = reported.clone();
+        let config = PollingConfig { on_error: Some(Arc::new(move |e| r.lock().unwrap().push(e.to_string()))), ..PollingConfig::default() };
+        let api_error = |error_code| TelegramError::Api { error_code, description: "error".to_string(), parameters: None };
+        assert!(!config.report(&api_error(502)));
+        assert!(!config.report(&TelegramError::Decode("not json".to_string())));
+        assert!(config.report(&api_error(401)));
+        assert!(config.report(&api_error(409)));
+        assert_eq!(reported.lock().unwrap().len(), 4);
+        assert!(!PollingConfig::default().report(&api_error(429)));
+    }
+
+    #[test]
+    fn test_sleep_while_running() {
+        let running = AtomicBool::new(false);
+        let start = std::time::Instant::now();
+        sleep_while_running(Duration::from_secs(10), &running);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,275 @@
+//! This module contains the rate limiter, which keeps a bot within the send limits of Telegram</br>
+//! Rate limiting is opt-in, e.g.:
+//! ```ignore
+//! let bot = Bot::new("your_bot_api_key".to_string()).with_rate_limiter(RateLimiter::new(RateLimits::default()));
+//! ```
+//! All calls sending or editing messages are queued per chat_id and globally, calls which would exceed
+//! a budget wait until a slot is free. Clones of a bot share the same limiter.
+
+use crate::objects::ChatId;
+use crate::request::Request;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Budget allows `requests` calls within every period of `per`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub requests: usize,
+    pub per: Duration,
+}
+
+impl Budget {
+    pub fn new(requests: usize, per: Duration) -> Budget {
+        Budget { requests, per }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimits {
+    /// budget for all chats together
+    pub global: Budget,
+    /// budget for every private chat, i.e. every positive chat_id
+    pub private_chat: Budget,
+    /// budget for every group, supergroup and channel, i.e. every negative chat_id or @username
+    pub group: Budget,
+}
+
+impl Default for RateLimits {
+    /// the limits documented by Telegram: 30 messages per second, 1 per second per private chat
+    /// and 20 per minute per group
+    fn default() -> Self {
+        RateLimits {
+            global: Budget::new(30, Duration::from_secs(1)),
+            private_chat: Budget::new(1, Duration::from_secs(1)),
+            group: Budget::new(20, Duration::from_secs(60)),
+        }
+    }
+}
+
+/// Clock is the source of time for the rate limiter, it can be replaced to test code using a limiter
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// SystemClock is the clock used by default
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Slots keeps the reserved instants of every budget sorted
+struct Slots {
+    global: VecDeque<Instant>,
+    chats: HashMap<ChatId, VecDeque<Instant>>,
+}
+
+pub struct RateLimiter {
+    limits: RateLimits,
+    clock: Box<dyn Clock>,
+    slots: Mutex<Slots>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> RateLimiter {
+        RateLimiter::with_clock(limits, SystemClock)
+    }
+
+    pub fn with_clock<C: Clock + 'static>(limits: RateLimits, clock: C) -> RateLimiter {
+        RateLimiter {
+            limits,
+            clock: Box::new(clock),
+            slots: Mutex::new(Slots { global: VecDeque::new(), chats: HashMap::new() }),
+        }
+    }
+
+    /// reserve books the earliest slot for a call to `chat_id` and returns how long to wait before sending.
+    /// Calls without chat_id, e.g. edits of inline messages, only count towards the global budget.
+    pub fn reserve(&self, chat_id: Option<ChatId>) -> Duration {
+        let now = self.clock.now();
+        let mut slots = self.slots.lock().unwrap();
+        let global = self.limits.global;
+        slots.global.retain(|&t| t + global.per > now);
+        slots.chats.retain(|id, times| {
+            let budget = self.budget(id);
+            times.retain(|&t| t + budget.per > now);
+            !times.is_empty()
+        });
+        let Slots { global: global_times, chats } = &mut *slots;
+        let at = match chat_id {
+            Some(id) => {
+                let budget = self.budget(&id);
+                let times = chats.entry(id).or_default();
+                let at = earliest(now, &[(&*global_times, global), (&*times, budget)]);
+                insert(times, at);
+                at
+            },
+            None => earliest(now, &[(&*global_times, global)])
+        };
+        insert(global_times, at);
+        at - now
+    }
+
+    /// wait_time reserves a slot for `request` if it is limited and returns how long to wait before sending it
+    pub(crate) fn wait_time(&self, request: &Request) -> Duration {
+        if request.is_rate_limited() {
+            self.reserve(request.chat_id())
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// budget returns the budget of `chat_id`, only private chats can't be addressed by @username
+    fn budget(&self, chat_id: &ChatId) -> Budget {
+        match chat_id {
+            ChatId::Id(id) if *id >= 0 => self.limits.private_chat,
+            _ => self.limits.group
+        }
+    }
+}
+
+/// earliest returns the first instant from `now` on, which fits into all budgets.
+/// A blocked instant moves on to the end of the period blocking it, all instants in between are blocked as well.
+fn earliest(now: Instant, queues: &[(&VecDeque<Instant>, Budget)]) -> Instant {
+    let mut at = now;
+    while let Some(next) = queues.iter().filter_map(|(times, budget)| blocked_until(times, *budget, at)).max() {
+        at = next;
+    }
+    at
+}
+
+/// blocked_until checks whether every period of `budget.per` still contains at most `budget.requests` calls
+/// after adding a call at `at`, if not it returns when the calls exceeding the budget stop blocking.
+/// Only the periods containing `at` can be exceeded, all others already fit.
+fn blocked_until(times: &VecDeque<Instant>, budget: Budget, at: Instant) -> Option<Instant> {
+    if budget.requests == 0 {
+        return None;
+    }
+    let index = times.partition_point(|&t| t <= at);
+    // the n-th instant after inserting `at`
+    let get = |n: usize| match n.cmp(&index) {
+        Ordering::Less => times[n],
+        Ordering::Equal => at,
+        Ordering::Greater => times[n - 1]
+    };
+    // every window of requests + 1 calls containing `at`, times[start] is its first call besides `at`
+    (index.saturating_sub(budget.requests)..=index)
+        .filter(|&start| start + budget.requests <= times.len())
+        .filter(|&start| get(start + budget.requests) - get(start) < budget.per)
+        .map(|start| times[start] + budget.per)
+        .max()
+}
+
+fn insert(times: &mut VecDeque<Instant>, at: Instant) {
+    let index = times.partition_point(|&t| t <= at);
+    times.insert(index, at);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    struct FakeClock {
+        start: Instant,
+        elapsed: Arc<Mutex<Duration>>,
+    }
+
+    impl FakeClock {
+        fn new() -> FakeClock {
+            FakeClock { start: Instant::now(), elapsed: Arc::new(Mutex::new(Duration::from_secs(0))) }
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.elapsed.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.lock().unwrap()
+        }
+    }
+
+    fn limiter(clock: &FakeClock) -> RateLimiter {
+        RateLimiter::with_clock(RateLimits::default(), clock.clone())
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_private_chat_budget() {
+        let clock = FakeClock::new();
+        let limiter = limiter(&clock);
+        assert_eq!(limiter.reserve(Some(1.into())), ms(0));
+        assert_eq!(limiter.reserve(Some(1.into())), ms(1000));
+        assert_eq!(limiter.reserve(Some(1.into())), ms(2000));
+        assert_eq!(limiter.reserve(Some(2.into())), ms(0));
+        clock.advance(ms(2500));
+        assert_eq!(limiter.reserve(Some(1.into())), ms(500));
+    }
+
+    #[test]
+    fn test_group_budget() {
+        let clock = FakeClock::new();
+        let limiter = limiter(&clock);
+        for _ in 0..20 {
+            assert_eq!(limiter.reserve(Some((-100).into())), ms(0));
+            clock.advance(ms(100));
+        }
+        assert_eq!(limiter.reserve(Some((-100).into())), ms(58000));
+    }
+
+    #[test]
+    fn test_username_budget() {
+        let clock = FakeClock::new();
+        let limiter = limiter(&clock);
+        for _ in 0..20 {
+            assert_eq!(limiter.reserve(Some("@channel".into())), ms(0));
+        }
+        assert_eq!(limiter.reserve(Some("@channel".into())), ms(60000));
+        assert_eq!(limiter.reserve(Some("@other".into())), ms(0));
+    }
+
+    #[test]
+    fn test_global_budget() {
+        let clock = FakeClock::new();
+        let limiter = limiter(&clock);
+        for chat_id in 1..=30 {
+            assert_eq!(limiter.reserve(Some(chat_id.into())), ms(0));
+        }
+        assert_eq!(limiter.reserve(Some(31.into())), ms(1000));
+        assert_eq!(limiter.reserve(None), ms(1000));
+        clock.advance(ms(1000));
+        assert_eq!(limiter.reserve(Some(32.into())), ms(0));
+    }
+
+    #[test]
+    fn test_waiting_chat_does_not_block_others() {
+        let clock = FakeClock::new();
+        let limits = RateLimits { global: Budget::new(3, ms(1000)), ..RateLimits::default() };
+        let limiter = RateLimiter::with_clock(limits, clock.clone());
+        assert_eq!(limiter.reserve(Some(1.into())), ms(0));
+        assert_eq!(limiter.reserve(Some(1.into())), ms(1000));
+        assert_eq!(limiter.reserve(Some(2.into())), ms(0));
+        clock.advance(ms(300));
+        assert_eq!(limiter.reserve(Some(3.into())), ms(0));
+        assert_eq!(limiter.reserve(Some(4.into())), ms(700));
+    }
+
+    #[test]
+    fn test_long_queue() {
+        let clock = FakeClock::new();
+        let limiter = limiter(&clock);
+        for i in 0..2000u64 {
+            assert_eq!(limiter.reserve(Some((-1).into())), ms(i / 20 * 60000));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Request building shared by the blocking and the async client.
+//! Every method collects its parameters into a `Request`, which only gets turned
+//! into an HTTP request by the client actually sending it.
+//! Parameters are sent as JSON body, or as multipart/form-data if there are files to upload.
+
+use serde::Deserialize;
+use serde_json::Value;
+use crate::*;
+use crate::objects::*;
+use std::time::Duration;
+
+/// Request is only public to be used by `TelegramMethod`, it can't be named outside of this crate
+#[derive(Clone)]
+pub struct Request {
+    pub(crate) method: &'static str,
+    pub(crate) parameters: Value,
+    pub(crate) files: Vec<(String, InputFile)>,
+}
+
+impl Request {
+    pub(crate) fn new(method: &'static str) -> Request {
+        Request {
+            method,
+            parameters: Value::Object(serde_json::Map::new()),
+            files: vec![],
+        }
+    }
+
+    pub(crate) fn add<P: Parameter>(&mut self, name: &'static str, value: P) {
+        value.add_to(parameter_name(name), self)
+    }
+
+    pub(crate) fn push(&mut self, name: &str, value: Value) {
+        self.parameters[name] = value;
+    }
+
+    /// migrate sends the request to `chat_id` instead, after the chat was upgraded to a supergroup.
+    /// It returns false if the request can't be migrated, i.e. it has no chat_id or also has a from_chat_id,
+    /// as the upgraded chat may as well be the one messages are taken from.
+    pub(crate) fn migrate(&mut self, chat_id: i64) -> bool {
+        if self.parameters.get("chat_id").is_none() || self.parameters.get("from_chat_id").is_some() {
+            return false;
+        }
+        self.parameters["chat_id"] = chat_id.into();
+        true
+    }
+
+    /// is_rate_limited tells whether the request sends or edits a message and counts towards the send limits
+    pub(crate) fn is_rate_limited(&self) -> bool {
+        MESSAGE_METHODS.contains(&self.method)
+    }
+
+    /// chat_id returns the chat_id or @username the request is sent to, if there is one
+    pub(crate) fn chat_id(&self) -> Option<ChatId> {
+        ChatId::deserialize(&self.parameters["chat_id"]).ok()
+    }
+
+    /// long_poll_timeout returns how long Telegram may keep the request open, as set by `timeout` of getUpdates
+    pub(crate) fn long_poll_timeout(&self) -> Option<Duration> {
+        self.parameters["timeout"].as_u64().map(Duration::from_secs)
+    }
+
+    /// url returns the full URL of the method, the parameters are sent in the body
+    pub(crate) fn url(&self, api_url: &str, key: &str) -> String {
+        format!("{}/bot{}/{}", api_url, key, self.method)
+    }
+
+    /// body returns all parameters as JSON object
+    pub(crate) fn body(&self) -> String {
+        self.parameters.to_string()
+    }
+
+    /// form_fields returns all parameters as text fields for a multipart/form-data request,
+    /// strings are sent as they are, everything else JSON-serialized
+    pub(crate) fn form_fields(&self) -> Vec<(String, String)> {
+        match &self.parameters {
+            Value::Object(parameters) => parameters.iter()
+                .map(|(name, value)| match value.as_str() {
+                    Some(s) => (name.to_string(), s.to_string()),
+                    None => (name.to_string(), value.to_string())
+                })
+                .collect(),
+            _ => vec![]
+        }
+    }
+}
+
+/// MESSAGE_METHODS are all methods sending or editing a message
+const MESSAGE_METHODS: &[&str] = &[
+    "sendMessage", "forwardMessage", "copyMessage", "sendPhoto", "sendAudio", "sendDocument", "sendVideo",
+    "sendAnimation", "sendVoice", "sendVideoNote", "sendMediaGroup", "sendLocation", "sendVenue", "sendContact",
+    "sendPoll", "sendDice", "sendSticker", "sendInvoice", "sendGame", "editMessageText", "editMessageCaption",
+    "editMessageMedia", "editMessageReplyMarkup", "editMessageLiveLocation", "stopMessageLiveLocation"
+];
+
+/// parameter_name maps the names of rust arguments to the names used by the Bot API
+fn parameter_name(name: &'static str) -> &'static str {
+    match name {
+        "typ" => "type",
+        _ => name
+    }
+}
+
+/// attach_media moves a file which has to be uploaded out of `media` into `files`
+/// and replaces it with the matching `attach://` reference
+pub(crate) fn attach_media(media: &mut InputMedia, files: &mut Vec<(String, InputFile)>) {
+    if media.media.is_upload() {
+        let name = format!("file{}", files.len());
+        let file = std::mem::replace(&mut media.media, InputFile::FileId(format!("attach://{}", name)));
+        files.push((name, file));
+    }
+}
+
+pub(crate) trait Parameter {
+    fn add_to(self, name: &'static str, request: &mut Request);
+}
+
+impl<P: Parameter> Parameter for Option<P> {
+    fn add_to(self, name: &'static str, request: &mut Request) {
+        if let Some(v) = self {
+            v.add_to(name, request)
+        }
+    }
+}
+
+impl Parameter for InputFile {
+    fn add_to(self, name: &'static str, request: &mut Request) {
+        if self.is_upload() {
+            request.files.push((name.to_string(), self))
+        } else {
+            request.push(name, Value::String(format!("{}", self)))
+        }
+    }
+}
+
+impl Parameter for InputMedia {
+    fn add_to(mut self, name: &'static str, request: &mut Request) {
+        attach_media(&mut self, &mut request.files);
+        request.push(name, serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+impl Parameter for Vec<InputMedia> {
+    fn add_to(mut self, name: &'static str, request: &mut Request) {
+        for media in self.iter_mut() {
+            attach_media(media, &mut request.files);
+        }
+        request.push(name, serde_json::to_value(self).unwrap_or_default())
+    }
+}
+
+expand_parameter! {
+    i64, i32, f64, bool, String, ChatId, ParseMode, ChatAction, PollType, DiceEmoji, Vec<String>, Vec<MessageEntity>, Vec<BotCommand>, ChatPermissions,
+    InlineKeyboardMarkup, ReplyMarkup, MaskPosition, Vec<InlineQueryResult>,
+    Vec<i32>, Vec<LabeledPrice>, Vec<ShippingOption>, Vec<PassportElementError>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_body() {
+        let mut request = Request::new("sendPoll");
+        request.add("chat_id", 1234i64);
+        request.add("question", "right & wrong?".to_string());
+        request.add("options", vec!["yes".to_string(), "no".to_string()]);
+        request.add("typ", Some("quiz".to_string()));
+        request.add("is_anonymous", None::<bool>);
+        assert_eq!(request.url("https://api.telegram.org", "KEY"), "https://api.telegram.org/botKEY/sendPoll");
+        assert_eq!(request.body(), r#"{"chat_id":1234,"question":"right & wrong?","options":["yes","no"],"type":"quiz"}"#);
+    }
+
+    #[test]
+    fn test_request_form_fields() {
+        let mut request = Request::new("sendPhoto");
+        request.add("chat_id", 1234i64);
+        request.add("photo", InputFile::Memory { file_name: "cat.jpg".to_string(), data: vec![1, 2, 3] });
+        request.add("caption", "a cat".to_string());
+        assert_eq!(request.files.len(), 1);
+        assert_eq!(request.form_fields(), vec![("chat_id".to_string(), "1234".to_string()),
+                                               ("caption".to_string(), "a cat".to_string())]);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut request = Request::new("sendMessage");
+        request.add("chat_id", 1234i64);
+        request.add("text", "hello".to_string());
+        assert!(request.migrate(-1001234));
+        assert_eq!(request.body(), r#"{"chat_id":-1001234,"text":"hello"}"#);
+        let mut request = Request::new("sendMessage");
+        request.add("chat_id", ChatId::from("@group"));
+        assert!(request.migrate(-1001234));
+        assert_eq!(request.chat_id(), Some(ChatId::Id(-1001234)));
+        let mut request = Request::new("forwardMessage");
+        request.add("chat_id", 1234i64);
+        request.add("from_chat_id", -1234i64);
+        assert!(!request.migrate(-1001234));
+        assert_eq!(request.chat_id(), Some(ChatId::Id(1234)));
+        let mut request = Request::new("getMe");
+        assert!(!request.migrate(-1001234));
+        assert_eq!(request.body(), "{}");
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        let mut request = Request::new("editMessageText");
+        request.add("inline_message_id", "1234".to_string());
+        assert!(request.is_rate_limited());
+        assert_eq!(request.chat_id(), None);
+        let mut request = Request::new("getChat");
+        request.add("chat_id", 1234i64);
+        assert!(!request.is_rate_limited());
+        assert!(!Request::new("sendChatAction").is_rate_limited());
+        assert!(!Request::new("editChatInviteLink").is_rate_limited());
+        assert_eq!(request.chat_id(), Some(ChatId::Id(1234)));
+        assert_eq!(request.long_poll_timeout(), None);
+        let mut request = Request::new("getUpdates");
+        request.add("timeout", 30);
+        assert_eq!(request.long_poll_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_attach_media() {
+        let mut uploaded = InputMedia::empty();
+        uploaded.media = InputFile::Memory { file_name: "cat.jpg".to_string(), data: vec![1, 2, 3] };
+        let mut reused = InputMedia::empty();
+        reused.media = InputFile::FileId("1234".to_string());
+        let mut request = Request::new("sendMediaGroup");
+        request.add("media", vec![uploaded, reused]);
+        assert_eq!(request.files.len(), 1);
+        assert_eq!(request.files[0].0, "file0");
+        assert_eq!(request.parameters["media"][0]["media"], "attach://file0");
+        assert_eq!(request.parameters["media"][1]["media"], "1234");
+    }
+}
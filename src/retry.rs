@@ -0,0 +1,98 @@
+//! This module contains the retry policy, which lets a bot handle flood control and chat migrations on its own</br>
+//! Retrying is opt-in, e.g.:
+//! ```ignore
+//! let bot = Bot::new("your_bot_api_key".to_string()).with_retry_policy(RetryPolicy::default());
+//! ```
+//! If Telegram answers with `retry_after`, the bot sleeps and sends the request again.
+//! If a group was upgraded to a supergroup, the request is sent again to `migrate_to_chat_id`.
+//! Requests with a `from_chat_id` return the error instead, the upgraded group may be the one they copy from.
+
+use crate::error::TelegramError;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// how often a request is sent at most, including the first attempt
+    pub max_attempts: u32,
+    /// how long the bot may sleep for a single request in total, longer waits return the error instead
+    pub max_wait: Duration,
+    /// whether requests are sent again to the new chat after a migration to a supergroup
+    pub follow_migrations: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            max_wait: Duration::from_secs(60),
+            follow_migrations: true,
+        }
+    }
+}
+
+/// Retry tells the client how to send a failed request again
+#[derive(Debug, PartialEq)]
+pub(crate) enum Retry {
+    After(Duration),
+    Migrate(i64),
+}
+
+impl RetryPolicy {
+    /// next decides whether `error` is worth another attempt, given the attempts made and the time waited so far
+    pub(crate) fn next(&self, error: &TelegramError, attempts: u32, waited: Duration) -> Option<Retry> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let parameters = match error {
+            TelegramError::Api { parameters: Some(p), .. } => p,
+            _ => return None
+        };
+        if let Some(retry_after) = parameters.retry_after {
+            let wait = Duration::from_secs(retry_after.max(0) as u64);
+            if waited + wait > self.max_wait {
+                return None;
+            }
+            return Some(Retry::After(wait));
+        }
+        match parameters.migrate_to_chat_id {
+            Some(chat_id) if self.follow_migrations => Some(Retry::Migrate(chat_id)),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ResponseParameters;
+
+    fn api_error(retry_after: Option<i32>, migrate_to_chat_id: Option<i64>) -> TelegramError {
+        let mut parameters = ResponseParameters::empty();
+        parameters.retry_after = retry_after;
+        parameters.migrate_to_chat_id = migrate_to_chat_id;
+        TelegramError::Api { error_code: 429, description: "Too Many Requests".to_string(), parameters: Some(parameters) }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.next(&api_error(Some(5), None), 1, Duration::from_secs(0)), Some(Retry::After(Duration::from_secs(5))));
+        assert_eq!(policy.next(&api_error(Some(5), None), 3, Duration::from_secs(0)), None);
+        assert_eq!(policy.next(&api_error(Some(30), None), 2, Duration::from_secs(40)), None);
+    }
+
+    #[test]
+    fn test_retry_migration() {
+        let mut policy = RetryPolicy::default();
+        assert_eq!(policy.next(&api_error(None, Some(-1001234)), 1, Duration::from_secs(0)), Some(Retry::Migrate(-1001234)));
+        policy.follow_migrations = false;
+        assert_eq!(policy.next(&api_error(None, Some(-1001234)), 1, Duration::from_secs(0)), None);
+    }
+
+    #[test]
+    fn test_no_retry() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.next(&api_error(None, None), 1, Duration::from_secs(0)), None);
+        assert_eq!(policy.next(&TelegramError::Decode("invalid".to_string()), 1, Duration::from_secs(0)), None);
+    }
+}
@@ -0,0 +1,39 @@
+//! This module contains helpers shared by the tests of several modules
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// the request lines and bodies received by fake_api
+pub(crate) type Received = Vec<(String, Vec<u8>)>;
+
+/// fake_api answers one request per entry of `bodies` and returns the URL to use as api_url
+/// together with a handle yielding the request lines and the bodies it received
+pub(crate) fn fake_api(bodies: Vec<&'static str>) -> (String, thread::JoinHandle<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || bodies.into_iter().map(|body| {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap() == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut request_body = vec![0; content_length];
+        reader.read_exact(&mut request_body).unwrap();
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        stream.write_all(response.as_bytes()).unwrap();
+        (request_line, request_body)
+    }).collect());
+    (url, handle)
+}
@@ -0,0 +1,286 @@
+//! This module contains a minimal HTTP server to receive updates via webhook</br>
+//! It is meant to run behind a reverse proxy doing TLS, e.g.:
+//! ```ignore
+//! let (tx, rx) = mpsc::channel();
+//! let server = WebhookServer::start("127.0.0.1:8443", "/my-secret-path", tx)?;
+//! let received = rx.recv().unwrap();
+//! println!("Got: {}", received);
+//! server.stop();
+//! ```
+//! Only POST requests to the secret path are accepted, everything else is answered with an error status.
+//! Updates which can't be decoded are accepted anyway and passed to the decode error handler, if there is one,
+//! so Telegram doesn't send them again and again.
+//! Connections are handled by a fixed number of worker threads, further ones wait until a worker is free.
+//! Every connection is closed after 30 seconds at the latest, bodies larger than `MAX_BODY_SIZE` are rejected.
+
+use crate::error::TelegramError;
+use crate::methods::DecodeErrorHandler;
+use crate::objects::Update;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// how long to wait for the next bytes of a request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// how long a connection may take in total, so slow clients can't keep it open
+const CONNECTION_DEADLINE: Duration = Duration::from_secs(30);
+/// the longest request or header line accepted
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+/// how many connections are handled at the same time
+const WORKERS: usize = 8;
+/// the largest body accepted, updates are much smaller
+pub const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+const BAD_REQUEST: &str = "400 Bad Request";
+
+pub struct WebhookServer {
+    local_addr: SocketAddr,
+    running: Arc<AtomicBool>,
+}
+
+impl WebhookServer {
+    /// start binds to `address` and hands incoming connections to a pool of worker threads.
+    /// Every update posted to `secret_path` is sent into `tx`.
+    pub fn start<A: ToSocketAddrs>(address: A, secret_path: &str, tx: Sender<Update>) -> io::Result<WebhookServer> {
+        WebhookServer::start_with_decode_error_handler(address, secret_path, tx, None)
+    }
+
+    /// start_with_decode_error_handler is like start, but calls `on_decode_error` with every posted update,
+    /// which could not be decoded
+    pub fn start_with_decode_error_handler<A: ToSocketAddrs>(address: A, secret_path: &str, tx: Sender<Update>,
+                                                             on_decode_error: Option<DecodeErrorHandler>) -> io::Result<WebhookServer> {
+        let listener = TcpListener::bind(address)?;
+        let local_addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let secret_path = Arc::new(secret_path.to_string());
+        // accepting blocks while all workers are busy and the queue is full
+        let (queue, connections) = mpsc::sync_channel::<TcpStream>(WORKERS);
+        let connections = Arc::new(Mutex::new(connections));
+        for _ in 0..WORKERS {
+            let (connections, tx, secret_path, running) = (connections.clone(), tx.clone(), secret_path.clone(), running.clone());
+            let on_decode_error = on_decode_error.clone();
+            thread::spawn(move || loop {
+                // the queue is only locked while waiting for the next connection
+                let stream = match connections.lock().unwrap().recv() {
+                    Ok(s) => s,
+                    Err(_) => return
+                };
+                if let Some(u) = handle_connection(stream, &secret_path, on_decode_error.as_ref()) {
+                    if tx.send(u).is_err() {
+                        running.store(false, Ordering::SeqCst);
+                    }
+                }
+            });
+        }
+        let still_running = running.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !still_running.load(Ordering::SeqCst) {
+                    return;
+                }
+                if let Ok(stream) = stream {
+                    if queue.send(stream).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(WebhookServer { local_addr, running })
+    }
+
+    /// local_addr returns the address the server is bound to, which is useful when binding to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// stop shuts the server down, requests already being handled are still finished
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        // wake up the listener, which is blocked in accept
+        TcpStream::connect(self.local_addr).ok();
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, secret_path: &str, on_decode_error: Option<&DecodeErrorHandler>) -> Option<Update> {
+    let (status, update) = match read_request(&stream, secret_path) {
+        Ok(value) => ("200 OK", decode_update(value, on_decode_error)),
+        Err(status) => (status, None)
+    };
+    let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+    stream.write_all(response.as_bytes()).ok();
+    update
+}
+
+/// decode_update decodes a posted update, if it can't be decoded it is passed to `on_decode_error` and skipped
+fn decode_update(value: Value, on_decode_error: Option<&DecodeErrorHandler>) -> Option<Update> {
+    match Update::deserialize(&value) {
+        Ok(update) => Some(update),
+        Err(e) => {
+            if let Some(on_decode_error) = on_decode_error {
+                on_decode_error(&value, &TelegramError::from(e));
+            }
+            None
+        }
+    }
+}
+
+/// read_request reads the JSON body posted to `secret_path` or returns the status to answer with.
+/// Requests to any other path are rejected before reading their headers.
+fn read_request(stream: &TcpStream, secret_path: &str) -> Result<Value, &'static str> {
+    let mut reader = BufReader::new(DeadlineReader { stream, deadline: Instant::now() + CONNECTION_DEADLINE });
+    let request_line = read_line(&mut reader).map_err(|_| BAD_REQUEST)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    if path != secret_path {
+        return Err("404 Not Found");
+    }
+    if method != "POST" {
+        return Err("405 Method Not Allowed");
+    }
+
+    let mut content_length = 0;
+    let mut headers = 0;
+    loop {
+        let header = read_line(&mut reader).map_err(|_| BAD_REQUEST)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(BAD_REQUEST);
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| BAD_REQUEST)?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err("413 Payload Too Large");
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|_| BAD_REQUEST)?;
+    serde_json::from_slice(&body).map_err(|_| BAD_REQUEST)
+}
+
+/// read_line reads one line of at most MAX_LINE_LENGTH bytes
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_LENGTH).read_line(&mut line)?;
+    if line.len() as u64 == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line is too long"));
+    }
+    Ok(line)
+}
+
+/// DeadlineReader fails all reads once `deadline` has passed
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "connection took too long"));
+        }
+        self.stream.set_read_timeout(Some(left.min(READ_TIMEOUT)))?;
+        self.stream.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn post(server: &WebhookServer, path: &str, body: &str) -> u16 {
+        let url = format!("http://{}{}", server.local_addr(), path);
+        let res = reqwest::blocking::Client::new().post(url).body(body.to_string()).send().unwrap();
+        res.status().as_u16()
+    }
+
+    #[test]
+    fn test_webhook_forwards_updates() {
+        let (tx, rx) = mpsc::channel();
+        let server = WebhookServer::start("127.0.0.1:0", "/secret", tx).unwrap();
+        let status = post(&server, "/secret", r#"{"update_id":10,"message":{"message_id":10,"date":5,"chat":{"id":12,"type":"private"}}}"#);
+        assert_eq!(status, 200);
+        let update = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(update.update_id, 10);
+        assert_eq!(update.message.unwrap().message_id, 10);
+        server.stop();
+    }
+
+    #[test]
+    fn test_webhook_more_connections_than_workers() {
+        let (tx, rx) = mpsc::channel();
+        let server = Arc::new(WebhookServer::start("127.0.0.1:0", "/secret", tx).unwrap());
+        let posts: Vec<_> = (0..WORKERS as i64 * 3).map(|i| {
+            let server = server.clone();
+            thread::spawn(move || post(&server, "/secret", &format!(r#"{{"update_id":{}}}"#, i)))
+        }).collect();
+        for post in posts {
+            assert_eq!(post.join().unwrap(), 200);
+        }
+        let mut received: Vec<i64> = (0..WORKERS * 3).map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap().update_id).collect();
+        received.sort_unstable();
+        assert_eq!(received, (0..WORKERS as i64 * 3).collect::<Vec<i64>>());
+        server.stop();
+    }
+
+    #[test]
+    fn test_webhook_skips_undecodable_updates() {
+        let (tx, rx) = mpsc::channel();
+        let (error_tx, error_rx) = mpsc::channel();
+        let on_decode_error: DecodeErrorHandler = Arc::new(move |value: &Value, _: &TelegramError| error_tx.send(value["update_id"].clone()).unwrap());
+        let server = WebhookServer::start_with_decode_error_handler("127.0.0.1:0", "/secret", tx, Some(on_decode_error)).unwrap();
+        assert_eq!(post(&server, "/secret", r#"{"update_id":10,"message":{"message_id":"not a number"}}"#), 200);
+        assert_eq!(error_rx.recv_timeout(Duration::from_secs(5)).unwrap(), 10);
+        assert_eq!(post(&server, "/secret", r#"{"update_id":11}"#), 200);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().update_id, 11);
+        server.stop();
+    }
+
+    #[test]
+    fn test_webhook_rejects_wrong_path_and_body() {
+        let (tx, rx) = mpsc::channel();
+        let server = WebhookServer::start("127.0.0.1:0", "/secret", tx).unwrap();
+        assert_eq!(post(&server, "/guessed", r#"{"update_id":10}"#), 404);
+        assert_eq!(post(&server, "/secret", "not json"), 400);
+        assert!(rx.try_recv().is_err());
+        server.stop();
+    }
+
+    fn raw_request(server: &WebhookServer, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok();
+        response
+    }
+
+    #[test]
+    fn test_webhook_limits() {
+        let (tx, _rx) = mpsc::channel();
+        let server = WebhookServer::start("127.0.0.1:0", "/secret", tx).unwrap();
+        // the body is never sent, so this only returns because the wrong path is rejected right away
+        let response = raw_request(&server, "POST /guessed HTTP/1.1\r\nContent-Length: 100\r\n");
+        assert!(response.starts_with("HTTP/1.1 404"));
+        let response = raw_request(&server, "POST /secret HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 413"));
+        let response = raw_request(&server, &format!("POST /secret HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(10000)));
+        assert!(response.starts_with("HTTP/1.1 400"));
+        server.stop();
+    }
+}